@@ -0,0 +1,9 @@
+use day_17::process_analyze;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    match process_analyze(input) {
+        Ok(output) => println!("{}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
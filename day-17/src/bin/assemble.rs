@@ -0,0 +1,12 @@
+use std::io::Read;
+
+use day_17::process_assemble;
+
+fn main() {
+    let mut source = String::new();
+    std::io::stdin().read_to_string(&mut source).unwrap();
+    match process_assemble(&source) {
+        Ok(output) => println!("{}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
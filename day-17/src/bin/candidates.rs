@@ -0,0 +1,11 @@
+use day_17::process_candidates;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    let mut args = std::env::args().skip(1).map(|a| a.parse::<u64>().unwrap());
+    let from = args.next().unwrap_or(0);
+    let to = args.next().unwrap_or(from + 8);
+    for (a, output) in process_candidates(input, from..to) {
+        println!("{}: {}", a, output);
+    }
+}
@@ -0,0 +1,6 @@
+use day_17::process_debug;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    process_debug(input, std::io::stdin().lock(), std::io::stdout()).unwrap();
+}
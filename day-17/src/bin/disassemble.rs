@@ -0,0 +1,7 @@
+use day_17::process_disassemble;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    let output = process_disassemble(input);
+    println!("{}", output);
+}
@@ -2,6 +2,8 @@ use day_17::process_part1;
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part1(input);
-    println!("Output: {:?}", output);
+    match process_part1(input) {
+        Ok(output) => println!("Output: {}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
@@ -2,6 +2,8 @@ use day_17::process_part2;
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part2(input);
-    println!("Output: {:?}", output);
+    match process_part2(input) {
+        Ok(a) => println!("Output: {}", a),
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
@@ -1,4 +1,12 @@
-use std::fmt::Display;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    io::{self, BufRead, Write},
+    ops::Range,
+};
+
+/// Upper bound of executed instructions before a program is considered to be stuck.
+const MAX_STEPS: usize = 1_000_000;
 
 struct Program {
     registers: Registers,
@@ -31,80 +39,1072 @@ impl Program {
         }
     }
 
-    fn step(&mut self) -> Option<&mut Self> {
-        let new_inst_point = self.registers.apply(
-            self.instructions.get(self.instr_pointer)?,
-            self.instructions.get(self.instr_pointer + 1)?,
-            &mut self.output,
+    /// Whether the instruction pointer left the program.
+    fn halted(&self) -> bool {
+        self.instr_pointer + 1 >= self.instructions.len()
+    }
+
+    /// Executes the instruction at the instruction pointer.
+    /// Returns `Ok(None)` once the instruction pointer left the program.
+    fn step(&mut self) -> Result<Option<&mut Self>, Fault> {
+        if self.halted() {
+            return Ok(None);
+        }
+        let (instruction, arg) = (
+            &self.instructions[self.instr_pointer],
+            &self.instructions[self.instr_pointer + 1],
         );
+        let new_inst_point = self
+            .registers
+            .apply(instruction, arg, &mut self.output)
+            .map_err(|kind| Fault {
+                kind,
+                instr_pointer: self.instr_pointer,
+            })?;
         if let Some(pointer) = new_inst_point {
             self.instr_pointer = pointer;
         } else {
             self.instr_pointer += 2;
         }
         // println!("{}", self);
-        Some(self)
+        Ok(Some(self))
     }
 
-    fn run(&mut self) -> Vec<u8> {
-        while self.step().is_some() {}
-        self.output.clone()
+    /// Runs until the program halts or faults. Fails if it did not halt after `max_steps` steps.
+    fn run(&mut self, max_steps: usize) -> Result<Vec<u8>, Fault> {
+        let mut steps = 0;
+        while !self.halted() {
+            if steps == max_steps {
+                return Err(Fault {
+                    kind: FaultKind::StepLimitExceeded(max_steps),
+                    instr_pointer: self.instr_pointer,
+                });
+            }
+            steps += 1;
+            self.step()?;
+        }
+        Ok(self.output.clone())
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FaultKind {
+    InvalidOperand(u8),
+    InvalidOpcode(u8),
+    ArithmeticOverflow(u64),
+    StepLimitExceeded(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Fault {
+    kind: FaultKind,
+    instr_pointer: usize,
+}
+impl Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            FaultKind::InvalidOperand(operand) => write!(f, "invalid operand {}", operand),
+            FaultKind::InvalidOpcode(opcode) => write!(f, "invalid opcode {}", opcode),
+            FaultKind::ArithmeticOverflow(exponent) => {
+                write!(f, "2^{} does not fit into a register", exponent)
+            }
+            FaultKind::StepLimitExceeded(limit) => {
+                write!(f, "program did not halt within {} steps", limit)
+            }
+        }?;
+        write!(f, " at {}", self.instr_pointer)
+    }
+}
+
+#[derive(Clone, Copy)]
 struct Registers {
     a: u64,
     b: u64,
     c: u64,
 }
 impl Registers {
-    fn combo_value(&self, combo: &u8) -> u64 {
+    fn get(&self, register: Register) -> u64 {
+        match register {
+            Register::A => self.a,
+            Register::B => self.b,
+            Register::C => self.c,
+        }
+    }
+
+    fn combo_value(&self, combo: &u8) -> Result<u64, FaultKind> {
         match combo {
-            0 => 0,
-            1 => 1,
-            2 => 2,
-            3 => 3,
-            4 => self.a,
-            5 => self.b,
-            6 => self.c,
-            7 => unreachable!("Combo operator 7 should not appear in valid program"),
-            _ => unreachable!(),
+            0 => Ok(0),
+            1 => Ok(1),
+            2 => Ok(2),
+            3 => Ok(3),
+            4 => Ok(self.a),
+            5 => Ok(self.b),
+            6 => Ok(self.c),
+            _ => Err(FaultKind::InvalidOperand(*combo)),
         }
     }
 
-    fn apply(&mut self, instruction: &u8, arg: &u8, output: &mut Vec<u8>) -> Option<usize> {
+    fn literal_value(literal: &u8) -> Result<u64, FaultKind> {
+        match literal {
+            0..=7 => Ok(*literal as u64),
+            _ => Err(FaultKind::InvalidOperand(*literal)),
+        }
+    }
+
+    /// Computes `A / 2^combo` as used by adv, bdv and cdv.
+    fn division(&self, combo: &u8) -> Result<u64, FaultKind> {
+        let exponent = self.combo_value(combo)?;
+        u32::try_from(exponent)
+            .ok()
+            .and_then(|e| u64::checked_pow(2, e))
+            .map(|d| self.a / d)
+            .ok_or(FaultKind::ArithmeticOverflow(exponent))
+    }
+
+    fn apply(
+        &mut self,
+        instruction: &u8,
+        arg: &u8,
+        output: &mut Vec<u8>,
+    ) -> Result<Option<usize>, FaultKind> {
         match instruction {
             0 => {
-                self.a /= u64::pow(2, self.combo_value(arg) as u32);
-                None
+                self.a = self.division(arg)?;
+                Ok(None)
             }
             1 => {
-                self.b ^= *arg as u64;
-                None
+                self.b ^= Registers::literal_value(arg)?;
+                Ok(None)
             }
             2 => {
-                self.b = self.combo_value(arg) % 8;
-                None
+                self.b = self.combo_value(arg)? % 8;
+                Ok(None)
+            }
+            3 => {
+                let target = Registers::literal_value(arg)?;
+                Ok((self.a != 0).then_some(target as usize))
             }
-            3 => (self.a != 0).then_some(*arg as usize),
             4 => {
                 self.b ^= self.c;
-                None
+                Ok(None)
             }
             5 => {
-                output.push((self.combo_value(arg) % 8) as u8);
-                None
+                output.push((self.combo_value(arg)? % 8) as u8);
+                Ok(None)
             }
             6 => {
-                self.b = self.a / u64::pow(2, self.combo_value(arg) as u32);
-                None
+                self.b = self.division(arg)?;
+                Ok(None)
             }
             7 => {
-                self.c = self.a / u64::pow(2, self.combo_value(arg) as u32);
-                None
+                self.c = self.division(arg)?;
+                Ok(None)
+            }
+            _ => Err(FaultKind::InvalidOpcode(*instruction)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combo {
+    Literal(u8),
+    A,
+    B,
+    C,
+    Reserved,
+}
+impl Combo {
+    fn decode(operand: u8) -> Option<Combo> {
+        match operand {
+            0..=3 => Some(Combo::Literal(operand)),
+            4 => Some(Combo::A),
+            5 => Some(Combo::B),
+            6 => Some(Combo::C),
+            7 => Some(Combo::Reserved),
+            _ => None,
+        }
+    }
+
+    fn encode(&self) -> u8 {
+        match self {
+            Combo::Literal(l) => *l,
+            Combo::A => 4,
+            Combo::B => 5,
+            Combo::C => 6,
+            Combo::Reserved => 7,
+        }
+    }
+}
+impl Display for Combo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combo::Literal(l) => write!(f, "{}", l),
+            Combo::A => write!(f, "A"),
+            Combo::B => write!(f, "B"),
+            Combo::C => write!(f, "C"),
+            Combo::Reserved => write!(f, "<reserved>"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Instruction {
+    Adv(Combo),
+    Bxl(u8),
+    Bst(Combo),
+    Jnz(u8),
+    /// The operand is read but ignored.
+    Bxc(u8),
+    Out(Combo),
+    Bdv(Combo),
+    Cdv(Combo),
+}
+impl Instruction {
+    fn decode(opcode: u8, operand: u8) -> Option<Instruction> {
+        let combo = Combo::decode(operand)?;
+        match opcode {
+            0 => Some(Instruction::Adv(combo)),
+            1 => Some(Instruction::Bxl(operand)),
+            2 => Some(Instruction::Bst(combo)),
+            3 => Some(Instruction::Jnz(operand)),
+            4 => Some(Instruction::Bxc(operand)),
+            5 => Some(Instruction::Out(combo)),
+            6 => Some(Instruction::Bdv(combo)),
+            7 => Some(Instruction::Cdv(combo)),
+            _ => None,
+        }
+    }
+
+    fn encode(&self) -> [u8; 2] {
+        match self {
+            Instruction::Adv(c) => [0, c.encode()],
+            Instruction::Bxl(l) => [1, *l],
+            Instruction::Bst(c) => [2, c.encode()],
+            Instruction::Jnz(l) => [3, *l],
+            Instruction::Bxc(l) => [4, *l],
+            Instruction::Out(c) => [5, c.encode()],
+            Instruction::Bdv(c) => [6, c.encode()],
+            Instruction::Cdv(c) => [7, c.encode()],
+        }
+    }
+
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Adv(_) => "adv",
+            Instruction::Bxl(_) => "bxl",
+            Instruction::Bst(_) => "bst",
+            Instruction::Jnz(_) => "jnz",
+            Instruction::Bxc(_) => "bxc",
+            Instruction::Out(_) => "out",
+            Instruction::Bdv(_) => "bdv",
+            Instruction::Cdv(_) => "cdv",
+        }
+    }
+}
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Adv(c)
+            | Instruction::Bst(c)
+            | Instruction::Out(c)
+            | Instruction::Bdv(c)
+            | Instruction::Cdv(c) => write!(f, "{} {}", self.mnemonic(), c),
+            Instruction::Bxl(l) | Instruction::Jnz(l) | Instruction::Bxc(l) => {
+                write!(f, "{} {}", self.mnemonic(), l)
+            }
+        }
+    }
+}
+
+/// Renders the instructions one per line, prefixed by their address.
+/// Pairs which can not be decoded and a dangling last opcode are shown as raw data.
+fn disassemble(instructions: &[u8]) -> String {
+    instructions
+        .chunks(2)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let decoded = match chunk {
+                [opcode, operand] => Instruction::decode(*opcode, *operand),
+                _ => None,
+            };
+            match decoded {
+                Some(instruction) => format!("{:>3}: {}", i * 2, instruction),
+                None => format!(
+                    "{:>3}: .data {}",
+                    i * 2,
+                    chunk
+                        .iter()
+                        .map(|b| b.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AsmError {
+    UnknownMnemonic { line: usize, mnemonic: String },
+    MissingOperand { line: usize },
+    TrailingInput { line: usize },
+    InvalidOperand { line: usize, operand: String },
+    ReservedCombo { line: usize },
+    LiteralOutOfRange { line: usize, value: u64 },
+    UnknownLabel { line: usize, label: String },
+    DuplicateLabel { line: usize, label: String },
+}
+impl Display for AsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsmError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {}: unknown mnemonic '{}'", line, mnemonic)
+            }
+            AsmError::MissingOperand { line } => write!(f, "line {}: missing operand", line),
+            AsmError::TrailingInput { line } => write!(f, "line {}: trailing input", line),
+            AsmError::InvalidOperand { line, operand } => {
+                write!(f, "line {}: invalid operand '{}'", line, operand)
+            }
+            AsmError::ReservedCombo { line } => {
+                write!(f, "line {}: combo operand 7 is reserved", line)
+            }
+            AsmError::LiteralOutOfRange { line, value } => {
+                write!(f, "line {}: literal {} is not in 0..=7", line, value)
+            }
+            AsmError::UnknownLabel { line, label } => {
+                write!(f, "line {}: unknown label '{}'", line, label)
+            }
+            AsmError::DuplicateLabel { line, label } => {
+                write!(f, "line {}: label '{}' is already defined", line, label)
+            }
+        }
+    }
+}
+
+fn parse_literal(line: usize, operand: &str) -> Result<u8, AsmError> {
+    let value = operand
+        .parse::<u64>()
+        .map_err(|_| AsmError::InvalidOperand {
+            line,
+            operand: operand.to_string(),
+        })?;
+    if value > 7 {
+        return Err(AsmError::LiteralOutOfRange { line, value });
+    }
+    Ok(value as u8)
+}
+
+fn parse_combo(line: usize, operand: &str) -> Result<Combo, AsmError> {
+    match operand {
+        "A" | "a" => Ok(Combo::A),
+        "B" | "b" => Ok(Combo::B),
+        "C" | "c" => Ok(Combo::C),
+        _ => match parse_literal(line, operand)? {
+            7 => Err(AsmError::ReservedCombo { line }),
+            l => Combo::decode(l).ok_or(AsmError::InvalidOperand {
+                line,
+                operand: operand.to_string(),
+            }),
+        },
+    }
+}
+
+/// Assembles a program written as one instruction per line, e.g. `adv 3`, `out B` or `jnz loop`.
+/// Comments start with `#` or `;`. A line may start with a label `name:` which `jnz` can jump to.
+/// The operand of `bxc` is ignored by the machine and thereby optional, defaulting to 0.
+fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut statements: Vec<(usize, &str, Vec<&str>)> = vec![];
+    for (i, l) in source.lines().enumerate() {
+        let line = i + 1;
+        let mut code = l.split(['#', ';']).next().unwrap().trim();
+        if let Some((label, rest)) = code.split_once(':') {
+            let label = label.trim();
+            if labels.insert(label, statements.len() * 2).is_some() {
+                return Err(AsmError::DuplicateLabel {
+                    line,
+                    label: label.to_string(),
+                });
+            }
+            code = rest.trim();
+        }
+        let mut words = code.split_whitespace();
+        if let Some(mnemonic) = words.next() {
+            statements.push((line, mnemonic, words.collect()));
+        }
+    }
+
+    let mut instructions: Vec<u8> = vec![];
+    for (line, mnemonic, operands) in statements {
+        let operand = match operands.as_slice() {
+            [] if mnemonic == "bxc" => "0",
+            [] => return Err(AsmError::MissingOperand { line }),
+            [operand] => operand,
+            _ => return Err(AsmError::TrailingInput { line }),
+        };
+        let instruction = match mnemonic {
+            "adv" => Instruction::Adv(parse_combo(line, operand)?),
+            "bxl" => Instruction::Bxl(parse_literal(line, operand)?),
+            "bst" => Instruction::Bst(parse_combo(line, operand)?),
+            "jnz" => match labels.get(operand) {
+                Some(&target) if target > 7 => {
+                    return Err(AsmError::LiteralOutOfRange {
+                        line,
+                        value: target as u64,
+                    })
+                }
+                Some(&target) => Instruction::Jnz(target as u8),
+                None if operand.chars().all(|c| c.is_ascii_digit()) => {
+                    Instruction::Jnz(parse_literal(line, operand)?)
+                }
+                None => {
+                    return Err(AsmError::UnknownLabel {
+                        line,
+                        label: operand.to_string(),
+                    })
+                }
+            },
+            "bxc" => Instruction::Bxc(parse_literal(line, operand)?),
+            "out" => Instruction::Out(parse_combo(line, operand)?),
+            "bdv" => Instruction::Bdv(parse_combo(line, operand)?),
+            "cdv" => Instruction::Cdv(parse_combo(line, operand)?),
+            _ => {
+                return Err(AsmError::UnknownMnemonic {
+                    line,
+                    mnemonic: mnemonic.to_string(),
+                })
+            }
+        };
+        instructions.extend(instruction.encode());
+    }
+    Ok(instructions)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Register {
+    A,
+    B,
+    C,
+}
+impl TryFrom<&str> for Register {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "A" | "a" => Ok(Register::A),
+            "B" | "b" => Ok(Register::B),
+            "C" | "c" => Ok(Register::C),
+            _ => Err(format!("unknown register '{}'", value)),
+        }
+    }
+}
+
+const DEBUGGER_HELP: &str = "commands:
+  break <ip>    set a breakpoint on an instruction pointer
+  delete <ip>   remove a breakpoint
+  step [n]      execute n instructions (default 1)
+  continue      run until a breakpoint, a watched register changes or the program halts
+  watch <reg>   stop when register A, B or C changes
+  unwatch <reg> stop watching a register
+  regs          show registers and instruction pointer
+  output        show the output so far
+  list          disassemble the program
+  reset         restart the program with its initial registers
+  quit          leave the debugger";
+
+struct Debugger {
+    program: Program,
+    initial: Registers,
+    breakpoints: BTreeSet<usize>,
+    watches: BTreeSet<Register>,
+}
+impl Debugger {
+    fn new(program: Program) -> Debugger {
+        Debugger {
+            initial: program.registers,
+            program,
+            breakpoints: BTreeSet::new(),
+            watches: BTreeSet::new(),
+        }
+    }
+
+    fn halted(&self) -> bool {
+        self.program.halted()
+    }
+
+    /// Trace line of the instruction at `instr_pointer` followed by the current state.
+    fn trace(&self, instr_pointer: usize) -> String {
+        let instruction = match self
+            .program
+            .instructions
+            .get(instr_pointer..instr_pointer + 2)
+        {
+            Some(&[opcode, operand]) => Instruction::decode(opcode, operand)
+                .map(|i| i.to_string())
+                .unwrap_or(format!(".data {},{}", opcode, operand)),
+            _ => "<halted>".to_string(),
+        };
+        format!(
+            "{:<16}{}",
+            format!("{}: {}", instr_pointer, instruction),
+            self.program
+        )
+    }
+
+    /// Executes one instruction and returns the trace line for it.
+    fn step(&mut self) -> Result<Option<String>, Fault> {
+        let instr_pointer = self.program.instr_pointer;
+        if self.program.step()?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.trace(instr_pointer)))
+    }
+
+    fn cont(&mut self) -> String {
+        for i in 0..MAX_STEPS {
+            if self.halted() {
+                return "program halted".to_string();
+            }
+            if i > 0 && self.breakpoints.contains(&self.program.instr_pointer) {
+                return format!("breakpoint at {}", self.program.instr_pointer);
+            }
+            let instr_pointer = self.program.instr_pointer;
+            let before = self.program.registers;
+            if let Err(fault) = self.program.step() {
+                return format!("fault: {}", fault);
+            }
+            let changed: Vec<String> = self
+                .watches
+                .iter()
+                .filter(|r| self.program.registers.get(**r) != before.get(**r))
+                .map(|r| {
+                    format!(
+                        "{:?}: {} -> {}",
+                        r,
+                        before.get(*r),
+                        self.program.registers.get(*r)
+                    )
+                })
+                .collect();
+            if !changed.is_empty() {
+                return format!(
+                    "watch {}\n{}",
+                    changed.join(", "),
+                    self.trace(instr_pointer)
+                );
+            }
+        }
+        format!("no stop after {} steps", MAX_STEPS)
+    }
+
+    fn list(&self) -> String {
+        disassemble(&self.program.instructions)
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                let marker = if i * 2 == self.program.instr_pointer {
+                    '>'
+                } else {
+                    ' '
+                };
+                let breakpoint = if self.breakpoints.contains(&(i * 2)) {
+                    '*'
+                } else {
+                    ' '
+                };
+                format!("{}{}{}", breakpoint, marker, l)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Runs a single debugger command and returns the text to show.
+    fn execute(&mut self, command: &str) -> String {
+        let mut words = command.split_whitespace();
+        let Some(name) = words.next() else {
+            return String::new();
+        };
+        let arg = words.next();
+        let parse_ip = |arg: Option<&str>| {
+            arg.and_then(|a| a.parse::<usize>().ok())
+                .ok_or("expected an instruction pointer".to_string())
+        };
+        let parse_register =
+            |arg: Option<&str>| Register::try_from(arg.unwrap_or("")).map_err(|e| e.to_string());
+        let result: Result<String, String> = match name {
+            "b" | "break" => parse_ip(arg).map(|ip| {
+                self.breakpoints.insert(ip);
+                format!("breakpoint set at {}", ip)
+            }),
+            "d" | "delete" => parse_ip(arg).map(|ip| {
+                if self.breakpoints.remove(&ip) {
+                    format!("breakpoint at {} removed", ip)
+                } else {
+                    format!("no breakpoint at {}", ip)
+                }
+            }),
+            "s" | "step" => match arg.map(|a| a.parse::<usize>()).unwrap_or(Ok(1)) {
+                Ok(n) => {
+                    let mut traces: Vec<String> = vec![];
+                    for _ in 0..n {
+                        match self.step() {
+                            Ok(Some(trace)) => traces.push(trace),
+                            Ok(None) => break,
+                            Err(fault) => {
+                                traces.push(format!("fault: {}", fault));
+                                break;
+                            }
+                        }
+                    }
+                    if self.halted() {
+                        traces.push("program halted".to_string());
+                    }
+                    Ok(traces.join("\n"))
+                }
+                Err(_) => Err("expected a step count".to_string()),
+            },
+            "c" | "continue" => Ok(self.cont()),
+            "w" | "watch" => parse_register(arg).map(|r| {
+                self.watches.insert(r);
+                format!("watching {:?}", r)
+            }),
+            "unwatch" => parse_register(arg).map(|r| {
+                self.watches.remove(&r);
+                format!("stopped watching {:?}", r)
+            }),
+            "r" | "regs" => Ok(self.program.to_string()),
+            "o" | "output" => Ok(self
+                .program
+                .output
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(",")),
+            "l" | "list" => Ok(self.list()),
+            "reset" => {
+                self.program.registers = self.initial;
+                self.program.instr_pointer = 0;
+                self.program.output.clear();
+                Ok(self.program.to_string())
+            }
+            "h" | "help" => Ok(DEBUGGER_HELP.to_string()),
+            _ => Err(format!("unknown command '{}', try 'help'", name)),
+        };
+        result.unwrap_or_else(|e| format!("error: {}", e))
+    }
+}
+
+/// A combo operand resolved at compile time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operand {
+    Literal(u64),
+    Register(Register),
+}
+
+/// Pre-decoded instruction. Divisions by a literal power of two are turned into shifts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    AdvShift(u32),
+    AdvRegister(Register),
+    BdvShift(u32),
+    BdvRegister(Register),
+    CdvShift(u32),
+    CdvRegister(Register),
+    Bxl(u64),
+    Bst(Operand),
+    Jnz(usize),
+    Bxc,
+    Out(Operand),
+    Fault(FaultKind),
+}
+
+/// Program decoded once, to be executed for many different initial registers.
+/// Every address gets its own operation, so jumps into the middle of an instruction
+/// behave exactly like in the interpreter.
+struct CompiledProgram {
+    ops: Vec<Op>,
+}
+impl CompiledProgram {
+    fn compile(instructions: &[u8]) -> CompiledProgram {
+        let ops = instructions
+            .windows(2)
+            .map(|w| CompiledProgram::compile_instruction(w[0], w[1]))
+            .collect();
+        CompiledProgram { ops }
+    }
+
+    fn compile_instruction(opcode: u8, operand: u8) -> Op {
+        let combo = match operand {
+            0..=3 => Ok(Operand::Literal(operand as u64)),
+            4 => Ok(Operand::Register(Register::A)),
+            5 => Ok(Operand::Register(Register::B)),
+            6 => Ok(Operand::Register(Register::C)),
+            _ => Err(FaultKind::InvalidOperand(operand)),
+        };
+        let literal = Registers::literal_value(&operand);
+        let op = match opcode {
+            0 => combo.map(|c| match c {
+                Operand::Literal(l) => Op::AdvShift(l as u32),
+                Operand::Register(r) => Op::AdvRegister(r),
+            }),
+            1 => literal.map(Op::Bxl),
+            2 => combo.map(Op::Bst),
+            3 => literal.map(|l| Op::Jnz(l as usize)),
+            4 => Ok(Op::Bxc),
+            5 => combo.map(Op::Out),
+            6 => combo.map(|c| match c {
+                Operand::Literal(l) => Op::BdvShift(l as u32),
+                Operand::Register(r) => Op::BdvRegister(r),
+            }),
+            7 => combo.map(|c| match c {
+                Operand::Literal(l) => Op::CdvShift(l as u32),
+                Operand::Register(r) => Op::CdvRegister(r),
+            }),
+            _ => Err(FaultKind::InvalidOpcode(opcode)),
+        };
+        op.unwrap_or_else(Op::Fault)
+    }
+
+    /// Same semantics as `Program::run`, writing the output into `output`.
+    fn run_into(
+        &self,
+        registers: &mut Registers,
+        output: &mut Vec<u8>,
+        max_steps: usize,
+    ) -> Result<(), Fault> {
+        let Registers {
+            mut a,
+            mut b,
+            mut c,
+        } = *registers;
+        let mut instr_pointer = 0;
+        let mut result = Ok(());
+        let read = |r: Register, a: u64, b: u64, c: u64| match r {
+            Register::A => a,
+            Register::B => b,
+            Register::C => c,
+        };
+        let shift = |a: u64, exponent: u64| {
+            if exponent < 64 {
+                Ok(a >> exponent)
+            } else {
+                Err(FaultKind::ArithmeticOverflow(exponent))
+            }
+        };
+        let mut steps = 0;
+        while let Some(op) = self.ops.get(instr_pointer) {
+            if steps == max_steps {
+                result = Err(FaultKind::StepLimitExceeded(max_steps));
+                break;
+            }
+            steps += 1;
+            let step = match *op {
+                Op::AdvShift(s) => {
+                    a >>= s;
+                    Ok(None)
+                }
+                Op::AdvRegister(r) => shift(a, read(r, a, b, c)).map(|v| {
+                    a = v;
+                    None
+                }),
+                Op::BdvShift(s) => {
+                    b = a >> s;
+                    Ok(None)
+                }
+                Op::BdvRegister(r) => shift(a, read(r, a, b, c)).map(|v| {
+                    b = v;
+                    None
+                }),
+                Op::CdvShift(s) => {
+                    c = a >> s;
+                    Ok(None)
+                }
+                Op::CdvRegister(r) => shift(a, read(r, a, b, c)).map(|v| {
+                    c = v;
+                    None
+                }),
+                Op::Bxl(l) => {
+                    b ^= l;
+                    Ok(None)
+                }
+                Op::Bst(Operand::Literal(l)) => {
+                    b = l;
+                    Ok(None)
+                }
+                Op::Bst(Operand::Register(r)) => {
+                    b = read(r, a, b, c) % 8;
+                    Ok(None)
+                }
+                Op::Jnz(target) => Ok((a != 0).then_some(target)),
+                Op::Bxc => {
+                    b ^= c;
+                    Ok(None)
+                }
+                Op::Out(Operand::Literal(l)) => {
+                    output.push(l as u8);
+                    Ok(None)
+                }
+                Op::Out(Operand::Register(r)) => {
+                    output.push((read(r, a, b, c) % 8) as u8);
+                    Ok(None)
+                }
+                Op::Fault(kind) => Err(kind),
+            };
+            match step {
+                Ok(Some(target)) => instr_pointer = target,
+                Ok(None) => instr_pointer += 2,
+                Err(kind) => {
+                    result = Err(kind);
+                    break;
+                }
+            }
+        }
+        *registers = Registers { a, b, c };
+        result.map_err(|kind| Fault {
+            kind,
+            instr_pointer,
+        })
+    }
+}
+
+/// Symbolic register content within one loop iteration.
+/// For every bit we track the bits of A at the start of the iteration it may depend on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SymValue {
+    bits: [u64; 64],
+    max: u64,
+}
+impl SymValue {
+    fn a() -> SymValue {
+        let mut bits = [0; 64];
+        bits.iter_mut().enumerate().for_each(|(j, b)| *b = 1 << j);
+        SymValue {
+            bits,
+            max: u64::MAX,
+        }
+    }
+
+    fn literal(value: u64) -> SymValue {
+        SymValue {
+            bits: [0; 64],
+            max: value,
+        }
+    }
+
+    fn deps(&self) -> u64 {
+        self.bits.iter().fold(0, |acc, b| acc | b)
+    }
+
+    fn low_bits(&self) -> SymValue {
+        let mut bits = [0; 64];
+        bits[..3].copy_from_slice(&self.bits[..3]);
+        SymValue {
+            bits,
+            max: self.max.min(7),
+        }
+    }
+
+    fn xor(&self, other: &SymValue) -> SymValue {
+        let mut bits = self.bits;
+        bits.iter_mut().zip(other.bits).for_each(|(b, o)| *b |= o);
+        let max = self.max | other.max;
+        SymValue {
+            bits,
+            max: u64::MAX >> max.leading_zeros().min(63),
+        }
+    }
+
+    /// `self >> shift` where the shift amount itself may be symbolic.
+    fn shr(&self, shift: &SymValue) -> SymValue {
+        let shift_deps = shift.deps();
+        let max_shift = shift.max.min(63) as usize;
+        let min_shift = if shift_deps == 0 { max_shift } else { 0 };
+        let mut bits = [0; 64];
+        bits.iter_mut().enumerate().for_each(|(j, b)| {
+            *b = (min_shift..=max_shift)
+                .filter_map(|s| self.bits.get(j + s))
+                .fold(0, |acc, d| acc | d);
+            if *b != 0 {
+                *b |= shift_deps;
+            }
+        });
+        SymValue {
+            bits,
+            max: self.max >> min_shift,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AnalysisError {
+    InvalidInstruction(usize),
+    NotALoop,
+    UnsupportedJump(usize),
+    UnsupportedShift(usize),
+    MissingShift,
+    CarriedRegister(Register),
+    NoOutput,
+}
+impl Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::InvalidInstruction(ip) => write!(f, "invalid instruction at {}", ip),
+            AnalysisError::NotALoop => write!(f, "program does not end with jnz 0"),
+            AnalysisError::UnsupportedJump(ip) => write!(f, "unsupported jump at {}", ip),
+            AnalysisError::UnsupportedShift(ip) => {
+                write!(
+                    f,
+                    "A has to be shifted exactly once by a literal, see {}",
+                    ip
+                )
+            }
+            AnalysisError::MissingShift => write!(f, "A is never shifted"),
+            AnalysisError::CarriedRegister(r) => {
+                write!(f, "{:?} is read before it is written in the loop", r)
+            }
+            AnalysisError::NoOutput => write!(f, "loop has no output"),
+        }
+    }
+}
+
+/// Dependencies of the outputs of a straight-line loop ending in `jnz 0`,
+/// which shifts A by a constant each iteration.
+#[derive(Debug, PartialEq, Eq)]
+struct LoopAnalysis {
+    shift: u32,
+    /// For every `out` of the loop body, the bits of A at the start of the iteration it depends on.
+    outputs: Vec<u64>,
+}
+impl LoopAnalysis {
+    fn analyze(instructions: &[u8]) -> Result<LoopAnalysis, AnalysisError> {
+        let body: Vec<Instruction> = instructions
+            .chunks(2)
+            .enumerate()
+            .map(|(i, chunk)| match chunk {
+                [opcode, operand] => Instruction::decode(*opcode, *operand)
+                    .ok_or(AnalysisError::InvalidInstruction(i * 2)),
+                _ => Err(AnalysisError::InvalidInstruction(i * 2)),
+            })
+            .collect::<Result<_, _>>()?;
+        if body.last() != Some(&Instruction::Jnz(0)) {
+            return Err(AnalysisError::NotALoop);
+        }
+
+        let mut a = SymValue::a();
+        let mut b: Option<SymValue> = None;
+        let mut c: Option<SymValue> = None;
+        let mut shift = None;
+        let mut outputs = vec![];
+        for (i, instruction) in body[..body.len() - 1].iter().enumerate() {
+            let read_b = || b.ok_or(AnalysisError::CarriedRegister(Register::B));
+            let read_c = || c.ok_or(AnalysisError::CarriedRegister(Register::C));
+            let combo = |combo: &Combo| match combo {
+                Combo::Literal(l) => Ok(SymValue::literal(*l as u64)),
+                Combo::A => Ok(a),
+                Combo::B => read_b(),
+                Combo::C => read_c(),
+                Combo::Reserved => Err(AnalysisError::InvalidInstruction(i * 2)),
+            };
+            match instruction {
+                Instruction::Adv(Combo::Literal(l)) if shift.is_none() && *l > 0 => {
+                    shift = Some(*l as u32);
+                    a = a.shr(&SymValue::literal(*l as u64));
+                }
+                Instruction::Adv(_) => return Err(AnalysisError::UnsupportedShift(i * 2)),
+                Instruction::Bxl(l) => b = Some(read_b()?.xor(&SymValue::literal(*l as u64))),
+                Instruction::Bst(operand) => b = Some(combo(operand)?.low_bits()),
+                Instruction::Jnz(_) => return Err(AnalysisError::UnsupportedJump(i * 2)),
+                Instruction::Bxc(_) => b = Some(read_b()?.xor(&read_c()?)),
+                Instruction::Out(operand) => outputs.push(combo(operand)?.low_bits().deps()),
+                Instruction::Bdv(operand) => b = Some(a.shr(&combo(operand)?)),
+                Instruction::Cdv(operand) => c = Some(a.shr(&combo(operand)?)),
             }
-            _ => unreachable!("Invalid OP code"),
         }
+        let shift = shift.ok_or(AnalysisError::MissingShift)?;
+        if outputs.is_empty() {
+            return Err(AnalysisError::NoOutput);
+        }
+        Ok(LoopAnalysis { shift, outputs })
+    }
+
+    /// Bits of the initial A the `digit`-th output depends on.
+    fn influence(&self, digit: usize) -> u64 {
+        let iteration = digit / self.outputs.len();
+        self.outputs[digit % self.outputs.len()]
+            .checked_shl(iteration as u32 * self.shift)
+            .unwrap_or(0)
+    }
+
+    /// Smallest initial A which makes the program output exactly `target`.
+    /// An output only depends on the bits of A which are left at its iteration.
+    /// Hence, A is built from the highest bits downwards, one iteration at a time.
+    fn solve(&self, compiled: &CompiledProgram, b: u64, c: u64, target: &[u8]) -> Option<u64> {
+        if target.is_empty() || !target.len().is_multiple_of(self.outputs.len()) {
+            return None;
+        }
+        let iterations = target.len() / self.outputs.len();
+        self.solve_from(compiled, b, c, target, iterations, 0)
+    }
+
+    fn solve_from(
+        &self,
+        compiled: &CompiledProgram,
+        b: u64,
+        c: u64,
+        target: &[u8],
+        iteration: usize,
+        high_bits: u64,
+    ) -> Option<u64> {
+        if iteration == 0 {
+            return Some(high_bits);
+        }
+        let iteration = iteration - 1;
+        let start = iteration * self.outputs.len();
+        let mut output = vec![];
+        (0..1u64 << self.shift)
+            .filter_map(|low| high_bits.checked_shl(self.shift).map(|h| h | low))
+            // A leading zero chunk would end the program one iteration early.
+            .filter(|&candidate| candidate != 0 || iteration == 0)
+            .find_map(|candidate| {
+                output.clear();
+                compiled
+                    .run_into(
+                        &mut Registers { a: candidate, b, c },
+                        &mut output,
+                        MAX_STEPS,
+                    )
+                    .ok()?;
+                (output == target[start..])
+                    .then(|| self.solve_from(compiled, b, c, target, iteration, candidate))
+                    .flatten()
+            })
+    }
+}
+
+/// Renders a bit mask as ranges of bits, e.g. `A[0..10]`.
+fn bit_ranges(mask: u64) -> String {
+    let mut ranges = vec![];
+    let mut j = 0;
+    while j < 64 {
+        if mask >> j & 1 == 0 {
+            j += 1;
+            continue;
+        }
+        let start = j;
+        while j < 64 && mask >> j & 1 == 1 {
+            j += 1;
+        }
+        ranges.push(if j - start == 1 {
+            format!("A[{}]", start)
+        } else {
+            format!("A[{}..{}]", start, j)
+        });
+    }
+    if ranges.is_empty() {
+        "constant".to_string()
+    } else {
+        ranges.join(", ")
     }
 }
 
@@ -126,20 +1126,118 @@ fn parse_input(input: &str) -> Program {
     Program::new(a, b, c, &instructions)
 }
 
-pub fn process_part1(input: &str) -> String {
-    parse_input(input)
-        .run()
-        .into_iter()
+pub fn process_part1(input: &str) -> Result<String, String> {
+    let output = parse_input(input)
+        .run(MAX_STEPS)
+        .map_err(|fault| fault.to_string())?;
+    Ok(output
+        .iter()
         .map(|i| i.to_string())
-        .reduce(|acc, c| acc + "," + &c)
-        .unwrap()
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+pub fn process_disassemble(input: &str) -> String {
+    disassemble(&parse_input(input).instructions)
+}
+
+pub fn process_assemble(source: &str) -> Result<String, String> {
+    let instructions = assemble(source).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "Program: {}",
+        instructions
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    ))
+}
+
+/// Interactive debugger reading commands line by line until `quit` or the end of the input.
+pub fn process_debug(input: &str, commands: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    let mut debugger = Debugger::new(parse_input(input));
+    writeln!(out, "{}", debugger.list())?;
+    write!(out, "(dbg) ")?;
+    out.flush()?;
+    for command in commands.lines() {
+        let command = command?;
+        if matches!(command.trim(), "q" | "quit") {
+            break;
+        }
+        let response = debugger.execute(&command);
+        if !response.is_empty() {
+            writeln!(out, "{}", response)?;
+        }
+        write!(out, "(dbg) ")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+pub fn process_analyze(input: &str) -> Result<String, String> {
+    let program = parse_input(input);
+    let analysis = LoopAnalysis::analyze(&program.instructions).map_err(|e| e.to_string())?;
+    let mut report = vec![format!(
+        "A is shifted by {} bits per iteration, {} output(s) per iteration",
+        analysis.shift,
+        analysis.outputs.len()
+    )];
+    report.extend(
+        (0..program.instructions.len())
+            .map(|digit| format!("{:>3}: {}", digit, bit_ranges(analysis.influence(digit)))),
+    );
+    Ok(report.join("\n"))
+}
+
+/// Smallest initial value of A for which the program outputs exactly `target`.
+pub fn process_solve(input: &str, target: &[u8]) -> Result<u64, String> {
+    let program = parse_input(input);
+    LoopAnalysis::analyze(&program.instructions)
+        .map_err(|e| e.to_string())?
+        .solve(
+            &CompiledProgram::compile(&program.instructions),
+            program.registers.b,
+            program.registers.c,
+            target,
+        )
+        .ok_or_else(|| {
+            let target: Vec<String> = target.iter().map(|o| o.to_string()).collect();
+            format!("no initial value of A outputs {}", target.join(","))
+        })
 }
 
-pub fn process_part2(input: &str) -> u64 {
-    todo!()
+/// Runs the program once for every initial value of A in `candidates`,
+/// returning the output or the fault for each of them.
+pub fn process_candidates(input: &str, candidates: Range<u64>) -> Vec<(u64, String)> {
+    let program = parse_input(input);
+    let compiled = CompiledProgram::compile(&program.instructions);
+    let mut output = vec![];
+    candidates
+        .map(|a| {
+            output.clear();
+            let mut registers = Registers {
+                a,
+                ..program.registers
+            };
+            let result = match compiled.run_into(&mut registers, &mut output, MAX_STEPS) {
+                Ok(()) => output
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                Err(fault) => format!("fault: {}", fault),
+            };
+            (a, result)
+        })
+        .collect()
+}
+
+pub fn process_part2(input: &str) -> Result<u64, String> {
+    process_solve(input, &parse_input(input).instructions)
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
 
@@ -161,6 +1259,7 @@ Program: 0,3,5,4,3,0";
             Program::new(0, 0, 9, &vec![2, 6])
                 .step()
                 .unwrap()
+                .unwrap()
                 .registers
                 .b,
             1
@@ -170,14 +1269,19 @@ Program: 0,3,5,4,3,0";
     #[test]
     fn test_computer2() {
         assert_eq!(
-            Program::new(10, 0, 0, &vec![5, 0, 5, 1, 5, 4]).run(),
+            Program::new(10, 0, 0, &vec![5, 0, 5, 1, 5, 4])
+                .run(MAX_STEPS)
+                .unwrap(),
             vec![0, 1, 2]
         );
     }
     #[test]
     fn test_computer3() {
         let mut program = Program::new(2024, 0, 0, &vec![0, 1, 5, 4, 3, 0]);
-        assert_eq!(program.run(), vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
+        assert_eq!(
+            program.run(MAX_STEPS).unwrap(),
+            vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]
+        );
         assert_eq!(program.registers.a, 0);
     }
     #[test]
@@ -186,6 +1290,7 @@ Program: 0,3,5,4,3,0";
             Program::new(0, 29, 0, &vec![1, 7])
                 .step()
                 .unwrap()
+                .unwrap()
                 .registers
                 .b,
             26
@@ -197,6 +1302,7 @@ Program: 0,3,5,4,3,0";
             Program::new(0, 2024, 43690, &vec![4, 0])
                 .step()
                 .unwrap()
+                .unwrap()
                 .registers
                 .b,
             44354
@@ -206,36 +1312,392 @@ Program: 0,3,5,4,3,0";
     #[test]
     fn test_computer6() {
         let mut program = Program::new(2024, 0, 0, &vec![0, 1, 3, 0]);
-        assert_eq!(program.step().unwrap().registers.a, 1012);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 506);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 253);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 126);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 63);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 31);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 15);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 7);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 3);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 1);
-        program.step();
-        assert_eq!(program.step().unwrap().registers.a, 0);
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 1012);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 506);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 253);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 126);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 63);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 31);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 15);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 7);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 3);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 1);
+        program.step().unwrap();
+        assert_eq!(program.step().unwrap().unwrap().registers.a, 0);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble(&[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0]),
+            "  0: bst A
+  2: bxl 1
+  4: cdv B
+  6: bxc 4
+  8: bxl 4
+ 10: adv 3
+ 12: out B
+ 14: jnz 0"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_invalid() {
+        assert_eq!(
+            disassemble(&[0, 7, 8, 1, 5]),
+            "  0: adv <reserved>\n  2: .data 8,1\n  4: .data 5"
+        );
+    }
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(
+            assemble("adv 1\nout A\njnz 0").unwrap(),
+            vec![0, 1, 5, 4, 3, 0]
+        );
+        let source = "bst A    # B = A % 8
+bxl 1
+cdv B
+bxc 4
+bxl 4
+adv 3
+out B
+jnz 0";
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0]
+        );
+        assert_eq!(assemble("bxc").unwrap(), vec![4, 0]);
+        // The disassembly assembles back to the same program, the addresses being labels.
+        let instructions = vec![2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0];
+        assert_eq!(assemble(&disassemble(&instructions)).unwrap(), instructions);
+    }
+
+    #[test]
+    fn test_assemble_labels() {
+        let source = "; counts A down
+start:
+    adv 1
+loop: out A
+    adv 1
+    jnz loop
+    jnz start";
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![0, 1, 5, 4, 0, 1, 3, 2, 3, 0]
+        );
+        assert_eq!(
+            Program::new(10, 0, 0, &assemble(source).unwrap())
+                .run(MAX_STEPS)
+                .unwrap(),
+            vec![5, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(
+            assemble("adv 1\nout 7"),
+            Err(AsmError::ReservedCombo { line: 2 })
+        );
+        assert_eq!(
+            assemble("bxl 8"),
+            Err(AsmError::LiteralOutOfRange { line: 1, value: 8 })
+        );
+        assert_eq!(
+            assemble("jnz end"),
+            Err(AsmError::UnknownLabel {
+                line: 1,
+                label: "end".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("mul A"),
+            Err(AsmError::UnknownMnemonic {
+                line: 1,
+                mnemonic: "mul".to_string()
+            })
+        );
+        assert_eq!(assemble("out"), Err(AsmError::MissingOperand { line: 1 }));
+        assert_eq!(
+            assemble("a:\nadv 1\na: out A"),
+            Err(AsmError::DuplicateLabel {
+                line: 3,
+                label: "a".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_debugger() {
+        let mut debugger = Debugger::new(Program::new(2024, 0, 0, &[0, 1, 5, 4, 3, 0]));
+        assert_eq!(debugger.execute("break 4"), "breakpoint set at 4");
+        assert_eq!(debugger.execute("continue"), "breakpoint at 4");
+        assert_eq!(debugger.program.output, vec![4]);
+        assert_eq!(
+            debugger.execute("step"),
+            "4: jnz 0        A: 1012, B: 0, C: 0: P: 0 -> 4"
+        );
+        assert_eq!(debugger.execute("c"), "breakpoint at 4");
+        assert_eq!(debugger.execute("output"), "4,2");
+        assert_eq!(debugger.execute("delete 4"), "breakpoint at 4 removed");
+        assert_eq!(debugger.execute("c"), "program halted");
+        assert_eq!(debugger.execute("o"), "4,2,5,6,7,7,7,7,3,1,0");
+        assert_eq!(debugger.execute("step"), "program halted");
+        assert_eq!(debugger.execute("reset"), "A: 2024, B: 0, C: 0: P: 0 -> ");
+    }
+
+    #[test]
+    fn test_debugger_watch() {
+        let mut debugger = Debugger::new(Program::new(10, 0, 0, &[2, 4, 1, 1, 5, 5, 0, 1, 3, 0]));
+        assert_eq!(debugger.execute("watch A"), "watching A");
+        assert_eq!(
+            debugger.execute("c"),
+            "watch A: 10 -> 5\n6: adv 1        A: 5, B: 3, C: 0: P: 8 -> 3"
+        );
+        assert_eq!(debugger.execute("watch d"), "error: unknown register 'd'");
+        assert_eq!(
+            debugger.execute("list"),
+            "    0: bst A\n    2: bxl 1\n    4: out B\n    6: adv 1\n >  8: jnz 0"
+        );
+    }
+
+    #[test]
+    fn test_faults() {
+        assert_eq!(
+            Program::new(0, 0, 0, &[0, 1, 5, 7]).run(MAX_STEPS),
+            Err(Fault {
+                kind: FaultKind::InvalidOperand(7),
+                instr_pointer: 2
+            })
+        );
+        assert_eq!(
+            Program::new(0, 0, 0, &[8, 0]).run(MAX_STEPS),
+            Err(Fault {
+                kind: FaultKind::InvalidOpcode(8),
+                instr_pointer: 0
+            })
+        );
+        assert_eq!(
+            Program::new(1, 64, 0, &[6, 5]).run(MAX_STEPS),
+            Err(Fault {
+                kind: FaultKind::ArithmeticOverflow(64),
+                instr_pointer: 0
+            })
+        );
+        assert_eq!(
+            Program::new(1, 63, 0, &[6, 5]).run(MAX_STEPS).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            Program::new(1, 0, 0, &[5, 4, 3, 0]).run(100),
+            Err(Fault {
+                kind: FaultKind::StepLimitExceeded(100),
+                instr_pointer: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_step_limit_boundary() {
+        // Halting after exactly `max_steps` instructions is not exceeding the limit.
+        assert_eq!(Program::new(1, 0, 0, &[5, 4]).run(1), Ok(vec![1]));
+        assert_eq!(
+            Program::new(1, 0, 0, &[5, 4]).run(0),
+            Err(Fault {
+                kind: FaultKind::StepLimitExceeded(0),
+                instr_pointer: 0
+            })
+        );
+        assert_eq!(Program::new(1, 0, 0, &[]).run(0), Ok(vec![]));
+        assert_eq!(Program::new(1, 0, 0, &[5, 4, 5, 4]).run(2), Ok(vec![1, 1]));
+        assert_eq!(
+            Program::new(1, 0, 0, &[5, 4, 5, 4]).run(1),
+            Err(Fault {
+                kind: FaultKind::StepLimitExceeded(1),
+                instr_pointer: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_debugger_fault() {
+        let mut debugger = Debugger::new(Program::new(0, 0, 0, &[1, 1, 2, 7]));
+        assert_eq!(
+            debugger.execute("step 2"),
+            "0: bxl 1        A: 0, B: 1, C: 0: P: 2 -> \nfault: invalid operand 7 at 2"
+        );
+        assert_eq!(debugger.execute("c"), "fault: invalid operand 7 at 2");
+        let mut debugger = Debugger::new(Program::new(1, 0, 0, &[3, 0]));
+        assert_eq!(debugger.execute("c"), "no stop after 1000000 steps");
+    }
+
+    #[test]
+    fn test_compiled_matches_interpreter() {
+        let programs: [&[u8]; 6] = [
+            &[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0],
+            &[0, 1, 5, 4, 3, 0],
+            &[0, 3, 5, 4, 3, 0],
+            &[6, 5, 7, 4, 5, 6, 3, 1],
+            &[2, 6, 0, 7, 5, 5],
+            &[1, 9, 8, 0, 4, 9, 5, 5],
+        ];
+        for instructions in programs {
+            let compiled = CompiledProgram::compile(instructions);
+            for a in 0..500 {
+                for (b, c) in [(0, 0), (3, 70), (64, 9)] {
+                    let mut program = Program::new(a, b, c, instructions);
+                    let expected = program.run(1000);
+                    let mut registers = Registers { a, b, c };
+                    let mut output = vec![];
+                    let result = compiled.run_into(&mut registers, &mut output, 1000);
+                    assert_eq!(result, expected.clone().map(|_| ()));
+                    assert_eq!(output, program.output);
+                    assert_eq!(
+                        (registers.a, registers.b, registers.c),
+                        (
+                            program.registers.a,
+                            program.registers.b,
+                            program.registers.c
+                        )
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compiled_step_limit_boundary() {
+        let programs: [&[u8]; 4] = [
+            &[5, 4],
+            &[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0],
+            &[0, 1, 5, 4, 3, 0],
+            &[1, 9, 8, 0, 4, 9, 5, 5],
+        ];
+        for instructions in programs {
+            let compiled = CompiledProgram::compile(instructions);
+            for a in 1..200 {
+                let mut program = Program::new(a, 0, 0, instructions);
+                let mut steps: usize = 0;
+                while !program.halted() && program.step().is_ok() {
+                    steps += 1;
+                }
+                // Limits just at and just below the number of executed instructions.
+                for limit in [steps, steps.saturating_sub(1)] {
+                    let expected = Program::new(a, 0, 0, instructions).run(limit);
+                    let mut output = vec![];
+                    let result =
+                        compiled.run_into(&mut Registers { a, b: 0, c: 0 }, &mut output, limit);
+                    assert_eq!(result, expected.map(|_| ()));
+                }
+                if program.halted() {
+                    assert!(Program::new(a, 0, 0, instructions).run(steps).is_ok());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let analysis =
+            LoopAnalysis::analyze(&[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0]).unwrap();
+        assert_eq!(analysis.shift, 3);
+        assert_eq!(analysis.outputs, vec![0b11_1111_1111]);
+        assert_eq!(bit_ranges(analysis.influence(2)), "A[6..16]");
+        assert_eq!(bit_ranges(analysis.influence(20)), "A[60..64]");
+
+        let analysis = LoopAnalysis::analyze(&[0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(analysis.outputs, vec![0b111_000]);
+        assert_eq!(bit_ranges(analysis.influence(1)), "A[6..9]");
+
+        assert_eq!(
+            LoopAnalysis::analyze(&[0, 1, 5, 4]),
+            Err(AnalysisError::NotALoop)
+        );
+        assert_eq!(
+            LoopAnalysis::analyze(&[1, 1, 0, 3, 5, 5, 3, 0]),
+            Err(AnalysisError::CarriedRegister(Register::B))
+        );
+        assert_eq!(
+            LoopAnalysis::analyze(&[0, 4, 5, 4, 3, 0]),
+            Err(AnalysisError::UnsupportedShift(0))
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let instructions = [2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0];
+        let analysis = LoopAnalysis::analyze(&instructions).unwrap();
+        let compiled = CompiledProgram::compile(&instructions);
+        let target = Program::new(46337277, 0, 0, &instructions)
+            .run(MAX_STEPS)
+            .unwrap();
+        let a = analysis.solve(&compiled, 0, 0, &target).unwrap();
+        assert!(a <= 46337277);
+        assert_eq!(
+            Program::new(a, 0, 0, &instructions).run(MAX_STEPS).unwrap(),
+            target
+        );
+
+        let instructions = [0, 1, 5, 4, 3, 0];
+        let analysis = LoopAnalysis::analyze(&instructions).unwrap();
+        let compiled = CompiledProgram::compile(&instructions);
+        assert_eq!(analysis.solve(&compiled, 0, 0, &[1, 0]), Some(2));
+        assert_eq!(analysis.solve(&compiled, 0, 0, &[0]), Some(0));
+        assert_eq!(analysis.solve(&compiled, 0, 0, &[1, 1]), None);
+    }
+
+    #[test]
+    fn test_process_solve() {
+        assert_eq!(process_solve(EXAMPLE2, &[0, 3, 5, 4, 3, 0]), Ok(117440));
+        assert_eq!(process_solve(EXAMPLE2, &[1, 0]), Ok(8));
+        assert_eq!(
+            process_solve(EXAMPLE2, &[1, 1]),
+            Err("no initial value of A outputs 1,1".to_string())
+        );
+        assert_eq!(
+            process_solve(
+                "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4",
+                &[0]
+            ),
+            Err(AnalysisError::NotALoop.to_string())
+        );
+    }
+
+    #[test]
+    fn test_process_candidates() {
+        assert_eq!(
+            process_candidates(EXAMPLE2, 117439..117441),
+            vec![
+                (117439, "7,2,5,4,3,0".to_string()),
+                (117440, "0,3,5,4,3,0".to_string())
+            ]
+        );
     }
 
     #[test]
     fn test_process_part1() {
-        assert_eq!(process_part1(EXAMPLE), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(
+            process_part1(EXAMPLE),
+            Ok("4,6,3,5,6,3,5,2,1,0".to_string())
+        );
+        assert_eq!(
+            process_part1("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 3,0"),
+            Err("program did not halt within 1000000 steps at 0".to_string())
+        );
     }
 
     #[test]
     fn test_process_part2() {
-        assert_eq!(process_part2(EXAMPLE2), 117440);
+        assert_eq!(process_part2(EXAMPLE2), Ok(117440));
     }
 }
@@ -1,7 +1,9 @@
-use day_18::process_part1;
+use day_18::{process_part1, MemoryConfig};
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part1(input);
-    println!("Output: {:?}", output);
+    match process_part1(input, &MemoryConfig::default()) {
+        Some(output) => println!("Output: {}", output),
+        None => println!("The exit cannot be reached"),
+    }
 }
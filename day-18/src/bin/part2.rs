@@ -1,7 +1,9 @@
-use day_18::process_part2;
+use day_18::{process_part2, MemoryConfig};
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part2(input);
-    println!("Output: {:?}", output);
+    match process_part2(input, &MemoryConfig::default()) {
+        Some((index, (x, y))) => println!("Output: {},{} (byte {})", x, y, index),
+        None => println!("No byte blocks the exit"),
+    }
 }
@@ -0,0 +1,7 @@
+use day_18::{process_render, MemoryConfig};
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    let output = process_render(input, &MemoryConfig::default());
+    println!("{}", output);
+}
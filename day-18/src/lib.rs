@@ -1,6 +1,37 @@
-use std::collections::VecDeque;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
 
 type Field = Vec<Vec<Entry>>;
+type Path = Vec<(usize, usize)>;
+
+/// Layout of the memory space and how many bytes have fallen for part 1.
+pub struct MemoryConfig {
+    pub size: usize,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    pub bytes: usize,
+}
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        MemoryConfig {
+            size: 71,
+            start: (0, 0),
+            goal: (70, 70),
+            bytes: 1024,
+        }
+    }
+}
+impl MemoryConfig {
+    fn empty_field(&self) -> Field {
+        vec![vec![Entry::Unvisited; self.size]; self.size]
+    }
+
+    fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.size && y < self.size
+    }
+}
 
 fn parse_input(input: &str) -> Vec<(usize, usize)> {
     input
@@ -33,7 +64,7 @@ fn set(x: i32, y: i32, dist: u64, field: &mut Field) {
             match e {
                 Entry::Blocked => (),
                 Entry::Unvisited => *e = Entry::Dist(dist),
-                Entry::Dist(d) if *d < dist => *e = Entry::Dist(dist),
+                Entry::Dist(d) if dist < *d => *e = Entry::Dist(dist),
                 _ => (),
             }
         }
@@ -68,17 +99,37 @@ fn neighbors(x: i32, y: i32) -> Vec<(i32, i32)> {
     vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
 }
 
-fn bfs(field: &mut Field, field_size: usize) -> u64 {
+/// Walks back from the goal to a cell with distance 0.
+/// The predecessor of a cell is a neighbor whose distance plus the cost of entering the cell matches.
+/// Costs have to be positive.
+fn trace_back(x: i32, y: i32, field: &Field, cost: impl Fn(usize, usize) -> u64) -> Path {
+    let mut path = vec![(x as usize, y as usize)];
+    let (mut x, mut y) = (x, y);
+    let mut dist = get_dist(x, y, field).unwrap();
+    while dist > 0 {
+        dist -= cost(x as usize, y as usize);
+        (x, y) = neighbors(x, y)
+            .into_iter()
+            .find(|(xx, yy)| get_dist(*xx, *yy, field) == Some(dist))
+            .unwrap();
+        path.push((x as usize, y as usize));
+    }
+    path.reverse();
+    path
+}
+
+/// Shortest path from start to goal, including both.
+fn bfs(field: &mut Field, config: &MemoryConfig) -> Option<Path> {
     let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
-    queue.push_front((0, 0));
-    set(0, 0, 0, field);
+    let (start_x, start_y) = (config.start.0 as i32, config.start.1 as i32);
+    queue.push_front((start_x, start_y));
+    set(start_x, start_y, 0, field);
     while let Some((x, y)) = queue.pop_front() {
-        let dist = get_dist(x, y, field).unwrap();
-        if x + 1 == field_size as i32 && y + 1 == field_size as i32 {
-            return dist;
-        }
-        if x < 0 || y < 0 || x > field_size as i32 || y > field_size as i32 {
+        let Some(dist) = get_dist(x, y, field) else {
             continue;
+        };
+        if (x as usize, y as usize) == config.goal {
+            return Some(trace_back(x, y, field, |_, _| 1));
         }
         neighbors(x, y).into_iter().for_each(|(xx, yy)| {
             if walkable(xx, yy, dist + 1, field) {
@@ -87,27 +138,293 @@ fn bfs(field: &mut Field, field_size: usize) -> u64 {
             }
         });
     }
-    0
+    None
 }
 
-pub fn process_part1(input: &str) -> u64 {
-    let mut field: Field = vec![vec![Entry::Unvisited; 71]; 71];
-    populate_field(&mut field, &parse_input(input), 1024);
-    bfs(&mut field, 71)
+/// Best-first search from start to goal, where entering a cell costs `cost` and
+/// `heuristic` estimates the remaining cost to the goal.
+/// With a zero heuristic this is Dijkstra, with an admissible one A*.
+/// Returns the total cost and the path, or `None` if the goal can not be reached
+/// or entering a cell costs nothing.
+fn search(
+    field: &mut Field,
+    config: &MemoryConfig,
+    cost: impl Fn(usize, usize) -> u64,
+    heuristic: impl Fn(usize, usize) -> u64,
+) -> Option<(u64, Path)> {
+    let mut queue: BinaryHeap<Reverse<(u64, u64, i32, i32)>> = BinaryHeap::new();
+    let (start_x, start_y) = (config.start.0 as i32, config.start.1 as i32);
+    set(start_x, start_y, 0, field);
+    get_dist(start_x, start_y, field)?;
+    queue.push(Reverse((
+        heuristic(config.start.0, config.start.1),
+        0,
+        start_x,
+        start_y,
+    )));
+    while let Some(Reverse((_, dist, x, y))) = queue.pop() {
+        if get_dist(x, y, field) != Some(dist) {
+            // Outdated queue entry.
+            continue;
+        }
+        if (x as usize, y as usize) == config.goal {
+            return Some((dist, trace_back(x, y, field, &cost)));
+        }
+        for (xx, yy) in neighbors(x, y) {
+            // Only ask for the cost of cells on the grid which can be entered at all.
+            if !walkable(xx, yy, dist, field) {
+                continue;
+            }
+            let cell_cost = cost(xx as usize, yy as usize);
+            if cell_cost == 0 {
+                return None;
+            }
+            let new_dist = dist + cell_cost;
+            if walkable(xx, yy, new_dist, field) {
+                set(xx, yy, new_dist, field);
+                queue.push(Reverse((
+                    new_dist + heuristic(xx as usize, yy as usize),
+                    new_dist,
+                    xx,
+                    yy,
+                )));
+            }
+        }
+    }
+    None
 }
 
-pub fn process_part2(input: &str) -> String {
-    let mut n = 0;
-    let cords = parse_input(input);
-    loop {
-        let mut field: Field = vec![vec![Entry::Unvisited; 71]; 71];
-        populate_field(&mut field, &cords, n);
-        if bfs(&mut field, 71) == 0 {
-            let (x, y) = cords.get(n - 1).unwrap();
-            return format!("{},{}", x, y);
+fn a_star(field: &mut Field, config: &MemoryConfig) -> Option<Path> {
+    let (goal_x, goal_y) = config.goal;
+    search(
+        field,
+        config,
+        |_, _| 1,
+        |x, y| (x.abs_diff(goal_x) + y.abs_diff(goal_y)) as u64,
+    )
+    .map(|(_, path)| path)
+}
+
+fn dijkstra(
+    field: &mut Field,
+    config: &MemoryConfig,
+    cost: impl Fn(usize, usize) -> u64,
+) -> Option<(u64, Path)> {
+    search(field, config, cost, |_, _| 0)
+}
+
+/// Shortest path while the bytes keep falling, one per step, starting after `config.bytes` bytes.
+/// A cell can be entered as long as no byte has fallen on it yet. Since cells only ever get blocked,
+/// arriving as early as possible is always best and waiting never helps.
+fn falling_bfs(cords: &[(usize, usize)], config: &MemoryConfig) -> Option<Path> {
+    let mut fall_time: Vec<Vec<usize>> = vec![vec![usize::MAX; config.size]; config.size];
+    cords.iter().enumerate().rev().for_each(|(i, (x, y))| {
+        if let Some(t) = fall_time.get_mut(*y).and_then(|l| l.get_mut(*x)) {
+            *t = i;
+        }
+    });
+    let open = |x: i32, y: i32, steps: u64| {
+        x >= 0
+            && y >= 0
+            && fall_time
+                .get(y as usize)
+                .and_then(|l| l.get(x as usize))
+                .is_some_and(|t| *t >= config.bytes + steps as usize)
+    };
+
+    let mut field = config.empty_field();
+    let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
+    let (start_x, start_y) = (config.start.0 as i32, config.start.1 as i32);
+    if !open(start_x, start_y, 0) {
+        return None;
+    }
+    queue.push_back((start_x, start_y));
+    set(start_x, start_y, 0, &mut field);
+    while let Some((x, y)) = queue.pop_front() {
+        let dist = get_dist(x, y, &field).unwrap();
+        if (x as usize, y as usize) == config.goal {
+            return Some(trace_back(x, y, &field, |_, _| 1));
         }
-        n += 1;
+        neighbors(x, y).into_iter().for_each(|(xx, yy)| {
+            // The byte of the next step falls while we move there.
+            if open(xx, yy, dist + 1) && walkable(xx, yy, dist + 1, &field) {
+                set(xx, yy, dist + 1, &mut field);
+                queue.push_back((xx, yy));
+            }
+        });
     }
+    None
+}
+
+/// Draws fallen bytes as `#`, the path as `O` and a highlighted byte as `X`.
+fn render(
+    cords: &[(usize, usize)],
+    config: &MemoryConfig,
+    path: Option<&Path>,
+    highlight: Option<(usize, usize)>,
+) -> String {
+    let mut grid = vec![vec!['.'; config.size]; config.size];
+    let mut draw = |(x, y): (usize, usize), c: char| {
+        if let Some(e) = grid.get_mut(y).and_then(|l| l.get_mut(x)) {
+            *e = c;
+        }
+    };
+    cords.iter().for_each(|p| draw(*p, '#'));
+    path.into_iter().flatten().for_each(|p| draw(*p, 'O'));
+    highlight.into_iter().for_each(|p| draw(p, 'X'));
+    grid.into_iter()
+        .map(|l| l.into_iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn process_part1(input: &str, config: &MemoryConfig) -> Option<u64> {
+    let mut field = config.empty_field();
+    populate_field(&mut field, &parse_input(input), config.bytes);
+    bfs(&mut field, config).map(|p| p.len() as u64 - 1)
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+impl DisjointSet {
+    fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, x: usize, y: usize) {
+        let (mut x, mut y) = (self.find(x), self.find(y));
+        if x == y {
+            return;
+        }
+        if self.size[x] < self.size[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parent[y] = x;
+        self.size[x] += self.size[y];
+    }
+}
+
+/// Finds the first byte after which the exit can no longer be reached.
+/// Instead of searching a path after every byte, all bytes are dropped at once and then removed
+/// in reverse order, joining the freed cell with its free neighbors, until start and exit are connected.
+/// Returns the index of the byte and its coordinates.
+fn first_blocking_byte(
+    cords: &[(usize, usize)],
+    config: &MemoryConfig,
+) -> Option<(usize, (usize, usize))> {
+    let field_size = config.size;
+    if !config.contains(config.start) || !config.contains(config.goal) {
+        return None;
+    }
+    let index = |x: usize, y: usize| y * field_size + x;
+    // Index of the byte which blocks a cell first.
+    let mut blocked: Vec<Option<usize>> = vec![None; field_size * field_size];
+    cords
+        .iter()
+        .enumerate()
+        .filter(|(_, (x, y))| *x < field_size && *y < field_size)
+        .for_each(|(i, (x, y))| {
+            blocked[index(*x, *y)].get_or_insert(i);
+        });
+
+    let mut sets = DisjointSet::new(field_size * field_size);
+    let free_neighbors = |x: usize, y: usize, blocked: &[Option<usize>]| {
+        neighbors(x as i32, y as i32)
+            .into_iter()
+            .filter(|(xx, yy)| {
+                *xx >= 0 && *yy >= 0 && (*xx as usize) < field_size && (*yy as usize) < field_size
+            })
+            .map(|(xx, yy)| index(xx as usize, yy as usize))
+            .filter(|i| blocked[*i].is_none())
+            .collect::<Vec<usize>>()
+    };
+    for y in 0..field_size {
+        for x in 0..field_size {
+            if blocked[index(x, y)].is_none() {
+                free_neighbors(x, y, &blocked)
+                    .into_iter()
+                    .for_each(|n| sets.union(index(x, y), n));
+            }
+        }
+    }
+
+    let (start, goal) = (
+        index(config.start.0, config.start.1),
+        index(config.goal.0, config.goal.1),
+    );
+    let connected = |sets: &mut DisjointSet, blocked: &[Option<usize>]| {
+        blocked[start].is_none() && blocked[goal].is_none() && sets.find(start) == sets.find(goal)
+    };
+    if connected(&mut sets, &blocked) {
+        return None;
+    }
+    for (i, (x, y)) in cords.iter().enumerate().rev() {
+        if *x >= field_size || *y >= field_size || blocked[index(*x, *y)] != Some(i) {
+            continue;
+        }
+        blocked[index(*x, *y)] = None;
+        free_neighbors(*x, *y, &blocked)
+            .into_iter()
+            .for_each(|n| sets.union(index(*x, *y), n));
+        if connected(&mut sets, &blocked) {
+            return Some((i, (*x, *y)));
+        }
+    }
+    None
+}
+
+/// Index and coordinates of the first byte after which the exit can no longer be reached.
+pub fn process_part2(input: &str, config: &MemoryConfig) -> Option<(usize, (usize, usize))> {
+    first_blocking_byte(&parse_input(input), config)
+}
+
+pub fn process_a_star(input: &str, config: &MemoryConfig) -> Option<u64> {
+    let mut field = config.empty_field();
+    populate_field(&mut field, &parse_input(input), config.bytes);
+    a_star(&mut field, config).map(|p| p.len() as u64 - 1)
+}
+
+/// Minimal total cost of the cells entered on the way to the goal.
+/// `cost` is only called for cells on the grid and has to be positive.
+/// Returns `None` if the goal can not be reached or entering some cell costs nothing.
+pub fn process_weighted(
+    input: &str,
+    config: &MemoryConfig,
+    cost: impl Fn(usize, usize) -> u64,
+) -> Option<u64> {
+    let mut field = config.empty_field();
+    populate_field(&mut field, &parse_input(input), config.bytes);
+    dijkstra(&mut field, config, cost).map(|(c, _)| c)
+}
+
+pub fn process_falling(input: &str, config: &MemoryConfig) -> Option<u64> {
+    falling_bfs(&parse_input(input), config).map(|p| p.len() as u64 - 1)
+}
+
+/// Renders the last path to the goal right before the first blocking byte, which is drawn as `X`.
+/// Without a blocking byte, the path after all bytes have fallen is shown.
+pub fn process_render(input: &str, config: &MemoryConfig) -> String {
+    let cords = parse_input(input);
+    let (fallen, highlight) = match first_blocking_byte(&cords, config) {
+        Some((index, cord)) => (&cords[..index], Some(cord)),
+        None => (&cords[..], None),
+    };
+    let mut field = config.empty_field();
+    populate_field(&mut field, fallen, fallen.len());
+    render(fallen, config, bfs(&mut field, config).as_ref(), highlight)
 }
 
 #[cfg(test)]
@@ -140,15 +457,199 @@ mod tests {
 1,6
 2,0";
 
+    const EXAMPLE_CONFIG: MemoryConfig = MemoryConfig {
+        size: 7,
+        start: (0, 0),
+        goal: (6, 6),
+        bytes: 12,
+    };
+
     #[test]
     fn test_process_part1() {
-        let mut field: Field = vec![vec![Entry::Unvisited; 7]; 7];
-        populate_field(&mut field, &parse_input(EXAMPLE), 12);
-        assert_eq!(bfs(&mut field, 7), 22)
+        assert_eq!(process_part1(EXAMPLE, &EXAMPLE_CONFIG), Some(22));
+        let blocked = MemoryConfig {
+            bytes: 21,
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(process_part1(EXAMPLE, &blocked), None);
     }
 
     #[test]
     fn test_process_part2() {
-        assert_eq!(process_part2(EXAMPLE), "6,1");
+        assert_eq!(process_part2(EXAMPLE, &EXAMPLE_CONFIG), Some((20, (6, 1))));
+        // The first twelve bytes leave a path open.
+        let few: String = EXAMPLE.lines().take(12).collect::<Vec<_>>().join("\n");
+        assert_eq!(process_part2(&few, &EXAMPLE_CONFIG), None);
+    }
+
+    #[test]
+    fn test_endpoints_outside() {
+        let cords = parse_input(EXAMPLE);
+        for (start, goal) in [((0, 0), (7, 6)), ((0, 7), (6, 6)), ((9, 9), (9, 9))] {
+            let config = MemoryConfig {
+                start,
+                goal,
+                ..EXAMPLE_CONFIG
+            };
+            assert_eq!(first_blocking_byte(&cords, &config), None);
+            assert_eq!(process_part2(EXAMPLE, &config), None);
+            let mut field = config.empty_field();
+            assert_eq!(bfs(&mut field, &config), None);
+        }
+    }
+
+    #[test]
+    fn test_bfs_path() {
+        let mut field = EXAMPLE_CONFIG.empty_field();
+        let cords = parse_input(EXAMPLE);
+        populate_field(&mut field, &cords, 12);
+        let path = bfs(&mut field, &EXAMPLE_CONFIG).unwrap();
+        assert_eq!(path.len(), 23);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(6, 6)));
+        assert!(path
+            .windows(2)
+            .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+        assert!(path.iter().all(|p| !cords[..12].contains(p)));
+
+        let config = MemoryConfig {
+            goal: (0, 0),
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(bfs(&mut config.empty_field(), &config), Some(vec![(0, 0)]));
+
+        let mut field = EXAMPLE_CONFIG.empty_field();
+        populate_field(&mut field, &cords, 21);
+        assert_eq!(bfs(&mut field, &EXAMPLE_CONFIG), None);
+    }
+
+    #[test]
+    fn test_a_star() {
+        assert_eq!(process_a_star(EXAMPLE, &EXAMPLE_CONFIG), Some(22));
+        let mut field = EXAMPLE_CONFIG.empty_field();
+        populate_field(&mut field, &parse_input(EXAMPLE), 12);
+        let path = a_star(&mut field, &EXAMPLE_CONFIG).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(6, 6)));
+        let config = MemoryConfig {
+            bytes: 21,
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(process_a_star(EXAMPLE, &config), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        assert_eq!(
+            process_weighted(EXAMPLE, &EXAMPLE_CONFIG, |_, _| 1),
+            Some(22)
+        );
+        assert_eq!(
+            process_weighted(EXAMPLE, &EXAMPLE_CONFIG, |_, _| 3),
+            Some(66)
+        );
+        // Row 6 is expensive, so the detour over column 6 is taken instead.
+        let config = MemoryConfig {
+            bytes: 0,
+            ..EXAMPLE_CONFIG
+        };
+        let cost = |_: usize, y: usize| if y == 6 { 10 } else { 1 };
+        assert_eq!(process_weighted(EXAMPLE, &config, cost), Some(21));
+        let mut field = config.empty_field();
+        let (_, path) = dijkstra(&mut field, &config, cost).unwrap();
+        assert_eq!(path.iter().filter(|(_, y)| *y == 6).count(), 1);
+    }
+
+    #[test]
+    fn test_dijkstra_grid_cost() {
+        // A cost looked up in a grid must never be asked for cells outside of it.
+        let grid = vec![vec![2; EXAMPLE_CONFIG.size]; EXAMPLE_CONFIG.size];
+        assert_eq!(
+            process_weighted(EXAMPLE, &EXAMPLE_CONFIG, |x, y| grid[y][x]),
+            Some(44)
+        );
+    }
+
+    #[test]
+    fn test_dijkstra_zero_cost() {
+        assert_eq!(process_weighted(EXAMPLE, &EXAMPLE_CONFIG, |_, _| 0), None);
+        // A single free cell next to the start is enough.
+        let cost = |x: usize, y: usize| if (x, y) == (1, 0) { 0 } else { 1 };
+        assert_eq!(process_weighted(EXAMPLE, &EXAMPLE_CONFIG, cost), None);
+    }
+
+    #[test]
+    fn test_falling() {
+        let config = MemoryConfig {
+            bytes: 0,
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(process_falling(EXAMPLE, &config), Some(12));
+        // The straight paths get cut by bytes falling in front of us.
+        let config = MemoryConfig {
+            bytes: 4,
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(process_a_star(EXAMPLE, &config), Some(12));
+        assert_eq!(process_falling(EXAMPLE, &config), Some(22));
+        assert_eq!(process_falling(EXAMPLE, &EXAMPLE_CONFIG), None);
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(
+                &[(1, 0), (1, 1)],
+                &MemoryConfig {
+                    size: 3,
+                    start: (0, 0),
+                    goal: (2, 0),
+                    bytes: 2,
+                },
+                Some(&vec![
+                    (0, 0),
+                    (0, 1),
+                    (0, 2),
+                    (1, 2),
+                    (2, 2),
+                    (2, 1),
+                    (2, 0)
+                ]),
+                Some((1, 1)),
+            ),
+            "O#O\nOXO\nOOO"
+        );
+        assert_eq!(
+            process_render(EXAMPLE, &EXAMPLE_CONFIG),
+            "O..#OOO
+O##OO#X
+O#OO#OO
+OOO#OO#
+###OO##
+.##O###
+#.#OOOO"
+        );
+    }
+
+    #[test]
+    fn test_config() {
+        let config = MemoryConfig {
+            goal: (2, 0),
+            ..EXAMPLE_CONFIG
+        };
+        assert_eq!(process_part1(EXAMPLE, &config), Some(2));
+        assert_eq!(process_part2(EXAMPLE, &config), Some((21, (1, 0))));
+    }
+
+    #[test]
+    fn test_first_blocking_byte_matches_bfs() {
+        let cords = parse_input(EXAMPLE);
+        let (index, _) = first_blocking_byte(&cords, &EXAMPLE_CONFIG).unwrap();
+        (0..=cords.len()).for_each(|n| {
+            let mut field = EXAMPLE_CONFIG.empty_field();
+            populate_field(&mut field, &cords, n);
+            assert_eq!(bfs(&mut field, &EXAMPLE_CONFIG).is_some(), n <= index);
+        });
+        assert_eq!(first_blocking_byte(&cords[..20], &EXAMPLE_CONFIG), None);
     }
 }
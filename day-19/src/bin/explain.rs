@@ -0,0 +1,12 @@
+use day_19::{process_explain, process_warnings};
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    if let Ok(warnings) = process_warnings(input) {
+        warnings.iter().for_each(|w| eprintln!("Warning: {}", w));
+    }
+    match process_explain(input) {
+        Ok(output) => println!("{}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
@@ -1,7 +1,12 @@
-use day_19::process_part1;
+use day_19::{process_part1, process_warnings};
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part1(input);
-    println!("Output: {:?}", output);
+    if let Ok(warnings) = process_warnings(input) {
+        warnings.iter().for_each(|w| eprintln!("Warning: {}", w));
+    }
+    match process_part1(input) {
+        Ok(output) => println!("Output: {}", output),
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
@@ -1,7 +1,19 @@
-use day_19::process_part2;
+use day_19::{process_part2, process_part2_big, process_warnings, CountError};
 
 fn main() {
     let input = include_str!("../../input1.txt");
-    let output = process_part2(input);
-    println!("Output: {:?}", output);
+    if let Ok(warnings) = process_warnings(input) {
+        warnings.iter().for_each(|w| eprintln!("Warning: {}", w));
+    }
+    match process_part2(input) {
+        Ok(output) => println!("Output: {}", output),
+        Err(CountError::Overflow(e)) => {
+            println!("{}, counting with arbitrary precision", e);
+            match process_part2_big(input) {
+                Ok(output) => println!("Output: {}", output),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
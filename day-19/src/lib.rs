@@ -1,95 +1,481 @@
-use std::collections::HashMap;
+use std::fmt::Display;
 
 type Desing = Vec<char>;
+/// Designs, patterns and the warnings raised while parsing them.
+type Towels = (Vec<Desing>, Vec<Desing>, Vec<ParseWarning>);
 
-fn parse_input(input: &str) -> (Vec<Desing>, Vec<Desing>) {
-    let mut lines = input.lines();
-    let patterns: Vec<Desing> = lines
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .map(|p| p.split_once(",").unwrap_or((p, "")).0.chars().collect())
-        .collect();
-    lines.next();
-    let designs: Vec<Desing> = lines.map(|l| l.chars().collect()).collect();
-    (designs, patterns)
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidColor { line: usize, color: char },
+    MissingPatterns,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidColor { line, color } => write!(
+                f,
+                "line {}: invalid color '{}', expected one of {:?}",
+                line, color, COLORS
+            ),
+            ParseError::MissingPatterns => write!(f, "no towel patterns given"),
+        }
+    }
 }
 
-fn ends_with(pattern: &Desing, design: &Desing) -> bool {
-    if pattern.len() > design.len() {
-        return false;
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseWarning {
+    EmptyPattern { line: usize },
+    DuplicatePattern { line: usize, pattern: String },
+}
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::EmptyPattern { line } => {
+                write!(f, "line {}: empty pattern is ignored", line)
+            }
+            ParseWarning::DuplicatePattern { line, pattern } => {
+                write!(
+                    f,
+                    "line {}: duplicate pattern '{}' is ignored",
+                    line, pattern
+                )
+            }
+        }
     }
-    pattern
-        .iter()
-        .rev()
-        .zip(design.iter().rev().take(pattern.len()))
-        .all(|(x, y)| x == y)
 }
 
-fn is_creatable(design: &Desing, patterns: &Vec<Desing>) -> bool {
-    rec(&mut design.clone(), patterns)
+fn parse_colors(line: usize, text: &str) -> Result<Desing, ParseError> {
+    text.chars()
+        .map(|c| {
+            COLORS
+                .contains(&c)
+                .then_some(c)
+                .ok_or(ParseError::InvalidColor { line, color: c })
+        })
+        .collect()
+}
+
+/// Patterns are separated by commas and/or whitespace and may span several lines.
+/// A blank line separates them from the designs, one per line.
+fn parse_towels(input: &str) -> Result<Towels, ParseError> {
+    let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    let mut patterns: Vec<Desing> = vec![];
+    let mut warnings: Vec<ParseWarning> = vec![];
+    for (line, l) in lines.by_ref().take_while(|(_, l)| !l.is_empty()) {
+        let parts: Vec<&str> = l.split(',').collect();
+        for (i, part) in parts.iter().enumerate() {
+            let words: Vec<&str> = part.split_whitespace().collect();
+            // A comma at the end of a line continues the list on the next line.
+            if words.is_empty() && i + 1 < parts.len() {
+                warnings.push(ParseWarning::EmptyPattern { line });
+            }
+            for word in words {
+                let pattern = parse_colors(line, word)?;
+                if patterns.contains(&pattern) {
+                    warnings.push(ParseWarning::DuplicatePattern {
+                        line,
+                        pattern: word.to_string(),
+                    });
+                } else {
+                    patterns.push(pattern);
+                }
+            }
+        }
+    }
+    if patterns.is_empty() {
+        return Err(ParseError::MissingPatterns);
+    }
+    let designs: Vec<Desing> = lines
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(line, l)| parse_colors(line, l))
+        .collect::<Result<_, _>>()?;
+    Ok((designs, patterns, warnings))
+}
+
+fn parse_input(input: &str) -> Result<(Vec<Desing>, Vec<Desing>), ParseError> {
+    parse_towels(input).map(|(designs, patterns, _)| (designs, patterns))
+}
+
+const COLORS: [char; 5] = ['w', 'u', 'b', 'r', 'g'];
+
+#[derive(Clone, Default)]
+struct TrieNode {
+    /// Index of the child node per color, 0 if there is none.
+    children: [usize; COLORS.len()],
+    /// Index of the pattern ending in this node.
+    pattern: Option<usize>,
 }
 
-fn rec(design: &mut Desing, patterns: &Vec<Desing>) -> bool {
-    if design.is_empty() {
-        return true;
+/// Prefix tree over all towel patterns.
+struct Trie {
+    nodes: Vec<TrieNode>,
+}
+impl Trie {
+    fn new(patterns: &[Desing]) -> Trie {
+        let mut trie = Trie {
+            nodes: vec![TrieNode::default()],
+        };
+        patterns
+            .iter()
+            .enumerate()
+            .for_each(|(i, p)| trie.insert(p, i));
+        trie
     }
-    patterns.iter().any(|pat| {
-        if !ends_with(pat, design) {
-            return false;
+
+    /// Patterns with a color outside of `COLORS` can never match and are skipped.
+    fn insert(&mut self, pattern: &Desing, index: usize) {
+        let Some(colors) = pattern
+            .iter()
+            .map(|c| COLORS.iter().position(|x| x == c))
+            .collect::<Option<Vec<usize>>>()
+        else {
+            return;
+        };
+        let mut node = 0;
+        for color in colors {
+            if self.nodes[node].children[color] == 0 {
+                self.nodes.push(TrieNode::default());
+                self.nodes[node].children[color] = self.nodes.len() - 1;
+            }
+            node = self.nodes[node].children[color];
         }
-        let store = design.split_off(design.len() - pat.len());
-        let res = rec(design, patterns);
-        design.extend(store);
-        res
+        self.nodes[node].pattern.get_or_insert(index);
+    }
+
+    /// All patterns matching the design at `start`, as the end position of the match and the pattern index.
+    fn matches<'a>(
+        &'a self,
+        design: &'a [char],
+        start: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        design[start..]
+            .iter()
+            .scan(0, |node, c| {
+                let color = COLORS.iter().position(|x| x == c)?;
+                *node = self.nodes[*node].children[color];
+                (*node != 0).then_some(*node)
+            })
+            .enumerate()
+            .filter_map(move |(i, node)| self.nodes[node].pattern.map(|p| (start + i + 1, p)))
+    }
+}
+
+fn is_creatable(design: &Desing, trie: &Trie) -> bool {
+    creatable_suffixes(design, trie)[0]
+}
+
+/// Number type for counting arrangements, which grow exponentially with the design length.
+trait Count: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+impl Count for u128 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u128::checked_add(*self, *other)
+    }
+}
+
+/// Unsigned integer of arbitrary size. Counting only needs addition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigCount {
+    /// Little endian limbs in base 2^64.
+    limbs: Vec<u64>,
+}
+impl Count for BigCount {
+    fn zero() -> Self {
+        BigCount { limbs: vec![] }
+    }
+
+    fn one() -> Self {
+        BigCount { limbs: vec![1] }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = false;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let (sum, c1) = self
+                .limbs
+                .get(i)
+                .unwrap_or(&0)
+                .overflowing_add(*other.limbs.get(i).unwrap_or(&0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        Some(BigCount { limbs })
+    }
+}
+impl Display for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut limbs = self.limbs.clone();
+        let mut chunks: Vec<u64> = vec![];
+        while !limbs.is_empty() {
+            let mut remainder: u128 = 0;
+            for limb in limbs.iter_mut().rev() {
+                let current = (remainder << 64) | *limb as u128;
+                *limb = (current / CHUNK as u128) as u64;
+                remainder = current % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((highest, rest)) => {
+                write!(f, "{}", highest)?;
+                rest.iter().rev().try_for_each(|c| write!(f, "{:019}", c))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CountOverflow {
+    pub design: String,
+}
+impl Display for CountOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "arrangement count of '{}' overflows", self.design)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CountError {
+    Parse(ParseError),
+    Overflow(CountOverflow),
+}
+impl Display for CountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountError::Parse(e) => e.fmt(f),
+            CountError::Overflow(e) => e.fmt(f),
+        }
+    }
+}
+impl From<ParseError> for CountError {
+    fn from(e: ParseError) -> Self {
+        CountError::Parse(e)
+    }
+}
+impl From<CountOverflow> for CountError {
+    fn from(e: CountOverflow) -> Self {
+        CountError::Overflow(e)
+    }
+}
+
+/// Number of arrangements for every prefix of the design, extended from left to right.
+/// Fails if the count does not fit into `C`.
+fn count_creatable<C: Count>(design: &Desing, trie: &Trie) -> Result<C, CountOverflow> {
+    let overflow = || CountOverflow {
+        design: design.iter().collect(),
+    };
+    let mut ways = vec![C::zero(); design.len() + 1];
+    ways[0] = C::one();
+    for start in 0..design.len() {
+        if ways[start].is_zero() {
+            continue;
+        }
+        for (end, _) in trie.matches(design, start) {
+            ways[end] = ways[end].checked_add(&ways[start]).ok_or_else(overflow)?;
+        }
+    }
+    Ok(ways.swap_remove(design.len()))
+}
+
+fn sum_creatable<C: Count>(designs: &[Desing], trie: &Trie) -> Result<C, CountOverflow> {
+    designs.iter().try_fold(C::zero(), |acc, d| {
+        acc.checked_add(&count_creatable(d, trie)?)
+            .ok_or(CountOverflow {
+                design: d.iter().collect(),
+            })
     })
 }
 
-fn count_creatable(design: &Desing, patterns: &Vec<Desing>) -> usize {
-    rec_count(&mut design.clone(), patterns, &mut HashMap::new())
+/// Whether the design can be finished from each position.
+fn creatable_suffixes(design: &Desing, trie: &Trie) -> Vec<bool> {
+    let mut creatable = vec![false; design.len() + 1];
+    creatable[design.len()] = true;
+    for start in (0..design.len()).rev() {
+        creatable[start] = trie.matches(design, start).any(|(end, _)| creatable[end]);
+    }
+    creatable
 }
 
-fn rec_count(
-    design: &mut Desing,
-    patterns: &Vec<Desing>,
-    cache: &mut HashMap<Desing, usize>,
-) -> usize {
-    if design.is_empty() {
-        return 1;
+/// Lazily enumerates all arrangements of a design as sequences of pattern indices.
+/// Arrangements are produced in lexicographic order of these sequences.
+/// Positions from which the design can not be finished are never entered.
+struct Arrangements<'a> {
+    design: &'a Desing,
+    trie: &'a Trie,
+    creatable: Vec<bool>,
+    /// For every towel placed so far, the remaining alternatives at its position, in reverse order.
+    stack: Vec<Vec<(usize, usize)>>,
+    current: Vec<(usize, usize)>,
+}
+impl<'a> Arrangements<'a> {
+    fn new(design: &'a Desing, trie: &'a Trie) -> Arrangements<'a> {
+        let creatable = creatable_suffixes(design, trie);
+        let mut arrangements = Arrangements {
+            design,
+            trie,
+            creatable,
+            stack: vec![],
+            current: vec![],
+        };
+        if arrangements.creatable[0] {
+            let options = arrangements.options(0);
+            arrangements.stack.push(options);
+        }
+        arrangements
     }
-    if let Some(c) = cache.get(design) {
-        return *c;
+
+    fn options(&self, start: usize) -> Vec<(usize, usize)> {
+        let mut options: Vec<(usize, usize)> = self
+            .trie
+            .matches(self.design, start)
+            .filter(|(end, _)| self.creatable[*end])
+            .map(|(end, pattern)| (pattern, end))
+            .collect();
+        options.sort_unstable_by(|a, b| b.cmp(a));
+        options
     }
-    let c = patterns
-        .iter()
-        .filter_map(|pat| {
-            if !ends_with(pat, design) {
-                return None;
+}
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(options) = self.stack.last_mut() {
+            let Some((pattern, end)) = options.pop() else {
+                self.stack.pop();
+                self.current.pop();
+                continue;
+            };
+            self.current.truncate(self.stack.len() - 1);
+            self.current.push((pattern, end));
+            if end == self.design.len() {
+                return Some(self.current.iter().map(|(p, _)| *p).collect());
             }
-            let store = design.split_off(design.len() - pat.len());
-            let res = rec_count(design, patterns, cache);
-            design.extend(store);
-            Some(res)
-        })
-        .sum();
-    cache.insert(design.to_vec(), c);
-    c
+            let options = self.options(end);
+            self.stack.push(options);
+        }
+        None
+    }
+}
+
+/// Arrangement using the fewest towels.
+fn fewest_towels(design: &Desing, trie: &Trie) -> Option<Vec<usize>> {
+    // Fewest towels to finish the design from a position and the first towel to use.
+    let mut best: Vec<Option<(usize, usize, usize)>> = vec![None; design.len() + 1];
+    best[design.len()] = Some((0, 0, design.len()));
+    for start in (0..design.len()).rev() {
+        best[start] = trie
+            .matches(design, start)
+            .filter_map(|(end, pattern)| best[end].map(|(n, _, _)| (n + 1, pattern, end)))
+            .min();
+    }
+    let mut arrangement = vec![];
+    let mut position = 0;
+    while position < design.len() {
+        let (_, pattern, end) = best[position]?;
+        arrangement.push(pattern);
+        position = end;
+    }
+    Some(arrangement)
+}
+
+/// Longest prefix of the design which can be built from towels.
+fn creatable_prefix(design: &Desing, trie: &Trie) -> usize {
+    let mut reachable = vec![false; design.len() + 1];
+    reachable[0] = true;
+    for start in 0..design.len() {
+        if reachable[start] {
+            trie.matches(design, start)
+                .for_each(|(end, _)| reachable[end] = true);
+        }
+    }
+    reachable.iter().rposition(|r| *r).unwrap()
 }
 
-pub fn process_part1(input: &str) -> u64 {
-    let (designs, patterns) = parse_input(input);
-    designs
+fn pattern_string(arrangement: &[usize], patterns: &[Desing]) -> String {
+    arrangement
         .iter()
-        .filter(|d| is_creatable(d, &patterns))
-        .count() as u64
+        .map(|p| patterns[*p].iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
-pub fn process_part2(input: &str) -> u64 {
-    let (designs, patterns) = parse_input(input);
-    designs
+/// Shows for every design how it can be created or how far one gets.
+pub fn process_explain(input: &str) -> Result<String, ParseError> {
+    let (designs, patterns) = parse_input(input)?;
+    let trie = Trie::new(&patterns);
+    Ok(designs
         .iter()
-        .map(|d| count_creatable(d, &patterns))
-        .sum::<usize>() as u64
+        .map(|d| {
+            let name: String = d.iter().collect();
+            match (Arrangements::new(d, &trie).next(), fewest_towels(d, &trie)) {
+                (Some(first), Some(fewest)) => format!(
+                    "{}: {} arrangements, first: {}, fewest towels: {}",
+                    name,
+                    count_creatable::<BigCount>(d, &trie).unwrap(),
+                    pattern_string(&first, &patterns),
+                    pattern_string(&fewest, &patterns),
+                ),
+                _ => format!(
+                    "{}: not creatable, longest creatable prefix: '{}'",
+                    name,
+                    d[..creatable_prefix(d, &trie)].iter().collect::<String>()
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Warnings about patterns which were ignored while parsing.
+pub fn process_warnings(input: &str) -> Result<Vec<ParseWarning>, ParseError> {
+    parse_towels(input).map(|(_, _, warnings)| warnings)
+}
+
+pub fn process_part1(input: &str) -> Result<u64, ParseError> {
+    let (designs, patterns) = parse_input(input)?;
+    let trie = Trie::new(&patterns);
+    Ok(designs.iter().filter(|d| is_creatable(d, &trie)).count() as u64)
+}
+
+pub fn process_part2(input: &str) -> Result<u128, CountError> {
+    let (designs, patterns) = parse_input(input)?;
+    Ok(sum_creatable(&designs, &Trie::new(&patterns))?)
+}
+
+/// Like part 2, but counting with arbitrary precision.
+pub fn process_part2_big(input: &str) -> Result<BigCount, ParseError> {
+    let (designs, patterns) = parse_input(input)?;
+    Ok(sum_creatable(&designs, &Trie::new(&patterns)).unwrap())
 }
 
 #[cfg(test)]
@@ -109,11 +495,175 @@ bbrgwb";
 
     #[test]
     fn test_process_part1() {
-        assert_eq!(process_part1(EXAMPLE), 6);
+        assert_eq!(process_part1(EXAMPLE), Ok(6));
+    }
+
+    #[test]
+    fn test_trie_matches() {
+        let (_, patterns) = parse_input(EXAMPLE).unwrap();
+        let trie = Trie::new(&patterns);
+        let design: Desing = "bwurrg".chars().collect();
+        assert_eq!(
+            trie.matches(&design, 0).collect::<Vec<_>>(),
+            vec![(1, 2), (3, 4)]
+        );
+        assert_eq!(trie.matches(&design, 3).collect::<Vec<_>>(), vec![(4, 0)]);
+        assert_eq!(trie.matches(&design, 5).collect::<Vec<_>>(), vec![(6, 3)]);
+        assert_eq!(count_creatable(&design, &trie), Ok(1u128));
+        assert_eq!(count_creatable(&"ubwu".chars().collect(), &trie), Ok(0u128));
+        assert_eq!(count_creatable(&"gbbr".chars().collect(), &trie), Ok(4u128));
+    }
+
+    #[test]
+    fn test_trie_unknown_colors() {
+        let patterns: Vec<Desing> = ["r", "rx", "x", "b"]
+            .iter()
+            .map(|p| p.chars().collect())
+            .collect();
+        let trie = Trie::new(&patterns);
+        let design: Desing = "rxb".chars().collect();
+        assert_eq!(trie.matches(&design, 0).collect::<Vec<_>>(), vec![(1, 0)]);
+        assert_eq!(trie.matches(&design, 1).count(), 0);
+        assert_eq!(trie.matches(&design, 2).collect::<Vec<_>>(), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let (designs, patterns) = parse_input(EXAMPLE).unwrap();
+        let trie = Trie::new(&patterns);
+        for design in &designs {
+            let arrangements: Vec<Vec<usize>> = Arrangements::new(design, &trie).collect();
+            assert_eq!(
+                arrangements.len() as u128,
+                count_creatable(design, &trie).unwrap()
+            );
+            assert!(arrangements.windows(2).all(|w| w[0] < w[1]));
+            assert!(arrangements
+                .iter()
+                .all(|a| a.iter().flat_map(|p| patterns[*p].iter()).eq(design.iter())));
+        }
+        let design: Desing = "gbbr".chars().collect();
+        assert_eq!(
+            Arrangements::new(&design, &trie).collect::<Vec<_>>(),
+            vec![vec![3, 2, 2, 0], vec![3, 2, 7], vec![6, 2, 0], vec![6, 7]]
+        );
+        assert_eq!(fewest_towels(&design, &trie), Some(vec![6, 7]));
+        let design: Desing = "bbrgwb".chars().collect();
+        assert_eq!(Arrangements::new(&design, &trie).next(), None);
+        assert_eq!(fewest_towels(&design, &trie), None);
+        assert_eq!(creatable_prefix(&design, &trie), 4);
+    }
+
+    #[test]
+    fn test_process_explain() {
+        let explanation = process_explain(EXAMPLE).unwrap();
+        let mut lines = explanation.lines();
+        assert_eq!(
+            lines.next(),
+            Some("brwrr: 2 arrangements, first: b,r,wr,r, fewest towels: br,wr,r")
+        );
+        assert_eq!(
+            lines.nth(3),
+            Some("ubwu: not creatable, longest creatable prefix: ''")
+        );
+    }
+
+    #[test]
+    fn test_parse_towels() {
+        let (designs, patterns, warnings) =
+            parse_towels("r,wr b ,\n  g,,r bwu\n\nbrwrr\ngb\n").unwrap();
+        assert_eq!(
+            patterns,
+            vec![
+                vec!['r'],
+                vec!['w', 'r'],
+                vec!['b'],
+                vec!['g'],
+                vec!['b', 'w', 'u']
+            ]
+        );
+        assert_eq!(designs, vec!["brwrr".chars().collect(), vec!['g', 'b']]);
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning::EmptyPattern { line: 2 },
+                ParseWarning::DuplicatePattern {
+                    line: 2,
+                    pattern: "r".to_string()
+                }
+            ]
+        );
+        assert_eq!(
+            parse_towels("r, wx\n\nrr"),
+            Err(ParseError::InvalidColor {
+                line: 1,
+                color: 'x'
+            })
+        );
+        assert_eq!(
+            parse_towels("r, w\n\nrr\nrgbk"),
+            Err(ParseError::InvalidColor {
+                line: 4,
+                color: 'k'
+            })
+        );
+        assert_eq!(parse_towels("\nrr"), Err(ParseError::MissingPatterns));
+        let (designs, patterns, warnings) = parse_towels(EXAMPLE).unwrap();
+        assert_eq!((designs.len(), patterns.len(), warnings.len()), (8, 8, 0));
+        assert_eq!(
+            process_warnings("r, r\n\nrr"),
+            Ok(vec![ParseWarning::DuplicatePattern {
+                line: 1,
+                pattern: "r".to_string()
+            }])
+        );
+        assert_eq!(process_part1("r, r\n\nrr\nb"), Ok(1));
+        assert_eq!(process_part1("\nrr"), Err(ParseError::MissingPatterns));
     }
 
     #[test]
     fn test_process_part2() {
-        assert_eq!(process_part2(EXAMPLE), 16);
+        assert_eq!(process_part2(EXAMPLE), Ok(16));
+        assert_eq!(process_part2_big(EXAMPLE).unwrap().to_string(), "16");
+        assert_eq!(
+            process_part2("r, x\n\nr"),
+            Err(CountError::Parse(ParseError::InvalidColor {
+                line: 1,
+                color: 'x'
+            }))
+        );
+    }
+
+    #[test]
+    fn test_count_overflow() {
+        let trie = Trie::new(&[vec!['w'], vec!['w', 'w']]);
+        let design: Desing = vec!['w'; 185];
+        assert_eq!(
+            count_creatable::<u128>(&design, &trie),
+            Ok(332825110087067562321196029789634457848)
+        );
+        let design: Desing = vec!['w'; 200];
+        assert_eq!(
+            count_creatable::<u128>(&design, &trie),
+            Err(CountOverflow {
+                design: "w".repeat(200)
+            })
+        );
+        assert_eq!(
+            count_creatable::<BigCount>(&design, &trie)
+                .unwrap()
+                .to_string(),
+            "453973694165307953197296969697410619233826"
+        );
+        assert_eq!(BigCount::zero().to_string(), "0");
+        let input = format!("w, ww\n\n{}\n{}", "w".repeat(185), "w".repeat(184));
+        assert!(matches!(
+            process_part2(&input),
+            Err(CountError::Overflow(_))
+        ));
+        assert_eq!(
+            process_part2_big(&input).unwrap().to_string(),
+            "538522340430300790495419781092981030533"
+        );
     }
 }
@@ -0,0 +1,14 @@
+use day_22::{top_sequences, MarketConfig, Selection};
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    for selection in [Selection::First, Selection::Last, Selection::Highest] {
+        for horizon in [500, 1000, 2000, 4000] {
+            let config = MarketConfig { horizon, selection };
+            println!("{:?} sale within {} prices:", selection, horizon);
+            for (sequence, bananas) in top_sequences(input, 5, &config) {
+                println!("  {:?}: {}", sequence, bananas);
+            }
+        }
+    }
+}
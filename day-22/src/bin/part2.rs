@@ -3,5 +3,5 @@ use day_22::process_part2;
 fn main() {
     let input = include_str!("../../input1.txt");
     let output = process_part2(input);
-    println!("Output: {:?}", output);
+    println!("{}", output);
 }
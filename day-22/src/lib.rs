@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::{cmp::Reverse, fmt::Display};
 
 fn parse_input(input: &str) -> Vec<Secret> {
     input
@@ -33,53 +33,314 @@ impl Secret {
     }
 }
 
+const SECRET_BITS: usize = 24;
+const SECRET_MASK: u64 = (1 << SECRET_BITS) - 1;
+
+impl Secret {
+    /// Undoes one step of the generator, the inverse of `next`.
+    fn back(&mut self) -> &mut Secret {
+        let mut x = self.0 & SECRET_MASK;
+        // Each step `x ^= x << k` is undone by xoring all further shifts by multiples of k.
+        x = (x ^ x << 11 ^ x << 22) & SECRET_MASK;
+        x ^= x >> 5 ^ x >> 10 ^ x >> 15 ^ x >> 20;
+        x = (x ^ x << 6 ^ x << 12 ^ x << 18) & SECRET_MASK;
+        self.0 = x;
+        self
+    }
+}
+
+/// A linear map over GF(2)^24, stored as the images of the unit vectors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct BitMatrix([u64; SECRET_BITS]);
+impl BitMatrix {
+    fn apply(&self, v: u64) -> u64 {
+        (0..SECRET_BITS)
+            .filter(|j| v >> j & 1 == 1)
+            .fold(0, |acc, j| acc ^ self.0[j])
+    }
+
+    fn then(&self, other: &BitMatrix) -> BitMatrix {
+        BitMatrix(self.0.map(|column| other.apply(column)))
+    }
+}
+
+/// Since `mix` and `prune` only xor shifted copies modulo 2^24, one step of the generator
+/// is linear over GF(2)^24. Squaring its matrix repeatedly allows jumping ahead in O(log n).
+struct JumpTable {
+    /// The generator step applied 2^k times.
+    powers: Vec<BitMatrix>,
+}
+impl JumpTable {
+    fn new() -> JumpTable {
+        let step = BitMatrix(std::array::from_fn(|j| Secret(1 << j).nth(1).unwrap()));
+        let powers = (0..u64::BITS)
+            .scan(step, |m, _| {
+                let current = *m;
+                *m = m.then(m);
+                Some(current)
+            })
+            .collect();
+        JumpTable { powers }
+    }
+
+    /// The secret after `n` steps.
+    fn jump(&self, secret: u64, n: u64) -> u64 {
+        self.powers
+            .iter()
+            .enumerate()
+            .filter(|(k, _)| n >> k & 1 == 1)
+            .fold(secret & SECRET_MASK, |acc, (_, m)| m.apply(acc))
+    }
+}
+
+/// Recovers the secret `steps` steps before the given one.
+pub fn rewind_secret(secret: u64, steps: usize) -> u64 {
+    let mut sec = Secret(secret);
+    (0..steps).for_each(|_| {
+        sec.back();
+    });
+    sec.0
+}
+
 pub fn process_part1(input: &str) -> u64 {
+    let table = JumpTable::new();
     parse_input(input)
-        .iter_mut()
-        .map(|sec| sec.nth(2000).unwrap())
+        .iter()
+        .map(|sec| table.jump(sec.0, 2000))
         .sum()
 }
 
-fn score(sec: &mut Secret, to: usize, scoring: &mut HashMap<[i8; 4], u64>) {
-    let mut seen: HashSet<[i8; 4]> = HashSet::new();
-    let n_bannanas: Vec<i8> = sec.take(to).map(|n| (n % 10) as i8).collect();
-    let diffs: Vec<i8> = n_bannanas
-        .iter()
-        .as_slice()
-        .windows(2)
-        .map(|w| w[1] - w[0])
-        .collect();
-    diffs
-        .as_slice()
-        .windows(4)
-        .zip(n_bannanas.into_iter().skip(4))
-        .for_each(|(arr, n)| {
-            if !seen.contains(arr) {
-                scoring
-                    .entry([arr[0], arr[1], arr[2], arr[3]])
-                    .and_modify(|e| *e += n as u64)
-                    .or_insert(n as u64);
-                seen.insert([arr[0], arr[1], arr[2], arr[3]]);
+/// Number of possible sequences of four price changes, each in -9..=9.
+const SEQUENCES: usize = 19 * 19 * 19 * 19;
+
+/// Which price a buyer pays when a change sequence occurs more than once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// The monkey sells at the first occurrence, as in the puzzle.
+    First,
+    /// The monkey waits for the last occurrence within the horizon.
+    Last,
+    /// The monkey picks the occurrence with the highest price.
+    Highest,
+}
+impl Selection {
+    fn replaces(&self, old: u8, new: u8) -> bool {
+        match self {
+            Selection::First => false,
+            Selection::Last => true,
+            Selection::Highest => new > old,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MarketConfig {
+    /// Number of prices generated per buyer, including the initial one.
+    pub horizon: usize,
+    pub selection: Selection,
+}
+impl Default for MarketConfig {
+    fn default() -> Self {
+        MarketConfig {
+            horizon: 2000,
+            selection: Selection::First,
+        }
+    }
+}
+
+/// Bananas per change sequence over all buyers scored so far.
+struct Scoring {
+    bananas: Vec<u64>,
+    /// Generation of the last buyer a sequence was seen for.
+    seen: Vec<u32>,
+    /// Price the current buyer pays for a sequence, valid if seen in this generation.
+    price: Vec<u8>,
+    generation: u32,
+}
+impl Scoring {
+    fn new() -> Scoring {
+        Scoring {
+            bananas: vec![0; SEQUENCES],
+            seen: vec![0; SEQUENCES],
+            price: vec![0; SEQUENCES],
+            generation: 0,
+        }
+    }
+
+    /// Index of the sequence with the most bananas, preferring the smallest index on ties.
+    fn best(&self) -> (usize, u64) {
+        self.bananas
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, b)| (**b, Reverse(*i)))
+            .map(|(i, b)| (i, *b))
+            .unwrap()
+    }
+
+    /// The `k` best sequences, extended by all sequences tied with the last one.
+    fn top(&self, k: usize) -> Vec<(usize, u64)> {
+        if k == 0 {
+            return vec![];
+        }
+        let mut ranked: Vec<(usize, u64)> = self
+            .bananas
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, b)| *b > 0)
+            .collect();
+        ranked.sort_by_key(|(i, b)| (Reverse(*b), *i));
+        let Some(&(_, cutoff)) = ranked.get(k - 1) else {
+            return ranked;
+        };
+        let end = ranked.partition_point(|(_, b)| *b >= cutoff);
+        ranked.truncate(end.max(k));
+        ranked
+    }
+}
+
+/// Inverse of the base 19 encoding of four price changes.
+fn sequence_from_index(index: usize) -> [i8; 4] {
+    let mut sequence = [0; 4];
+    let mut rest = index;
+    sequence.iter_mut().rev().for_each(|c| {
+        *c = (rest % 19) as i8 - 9;
+        rest /= 19;
+    });
+    sequence
+}
+
+/// Price at which the monkey sells to this buyer when watching for the sequence.
+fn sell_price(sec: &mut Secret, to: usize, sequence: [i8; 4], selection: Selection) -> Option<u8> {
+    let prices: Vec<i8> = sec.take(to).map(|n| (n % 10) as i8).collect();
+    prices
+        .windows(5)
+        .filter(|w| (0..4).all(|i| w[i + 1] - w[i] == sequence[i]))
+        .map(|w| w[4] as u8)
+        .reduce(|old, new| {
+            if selection.replaces(old, new) {
+                new
+            } else {
+                old
             }
-        });
+        })
 }
 
-pub fn process_part2(input: &str) -> u64 {
-    let mut scoring: HashMap<[i8; 4], u64> = HashMap::new();
-    parse_input(input)
-        .iter_mut()
-        .for_each(|sec| score(sec, 2000, &mut scoring));
+#[derive(Debug, PartialEq, Eq)]
+pub struct MarketReport {
+    pub bananas: u64,
+    pub sequence: [i8; 4],
+    /// Initial secret of every buyer and the price the sequence first triggers at.
+    pub buyers: Vec<(u64, Option<u8>)>,
+}
+impl Display for MarketReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Sequence {:?} gets {} bananas",
+            self.sequence, self.bananas
+        )?;
+        self.buyers
+            .iter()
+            .try_for_each(|(secret, price)| match price {
+                Some(p) => writeln!(f, "{:>10}: {}", secret, p),
+                None => writeln!(f, "{:>10}: never", secret),
+            })
+    }
+}
+
+fn score(sec: &mut Secret, to: usize, selection: Selection, scoring: &mut Scoring) {
+    scoring.generation += 1;
+    let mut prices = sec.take(to).map(|n| (n % 10) as i8);
+    let Some(mut last) = prices.next() else {
+        return;
+    };
+    // The last four changes encoded as a number in base 19.
+    let mut index = 0;
+    prices.enumerate().for_each(|(i, price)| {
+        index = (index * 19 + (price - last + 9) as usize) % SEQUENCES;
+        last = price;
+        if i < 3 {
+            return;
+        }
+        let price = price as u8;
+        if scoring.seen[index] != scoring.generation {
+            scoring.seen[index] = scoring.generation;
+            scoring.price[index] = price;
+            scoring.bananas[index] += price as u64;
+        } else if selection.replaces(scoring.price[index], price) {
+            scoring.bananas[index] += price as u64;
+            scoring.bananas[index] -= scoring.price[index] as u64;
+            scoring.price[index] = price;
+        }
+    });
+}
+
+fn score_all(secrets: &[u64], config: &MarketConfig) -> Scoring {
+    let mut scoring = Scoring::new();
+    secrets.iter().for_each(|s| {
+        score(
+            &mut Secret(*s),
+            config.horizon,
+            config.selection,
+            &mut scoring,
+        )
+    });
     scoring
-        .iter()
-        .max_by_key(|(_, v)| *v)
-        .map(|(_, v)| *v)
-        .unwrap()
+}
+
+fn parse_secrets(input: &str) -> Vec<u64> {
+    parse_input(input).iter().map(|sec| sec.0).collect()
+}
+
+/// Total bananas bought from all buyers with the given change sequence.
+pub fn sequence_bananas(input: &str, sequence: [i8; 4], config: &MarketConfig) -> u64 {
+    parse_secrets(input)
+        .into_iter()
+        .filter_map(|s| sell_price(&mut Secret(s), config.horizon, sequence, config.selection))
+        .map(u64::from)
+        .sum()
+}
+
+/// The `k` most profitable change sequences, best first, including all ties with the k-th.
+pub fn top_sequences(input: &str, k: usize, config: &MarketConfig) -> Vec<([i8; 4], u64)> {
+    score_all(&parse_secrets(input), config)
+        .top(k)
+        .into_iter()
+        .map(|(index, bananas)| (sequence_from_index(index), bananas))
+        .collect()
+}
+
+pub fn process_part2(input: &str) -> MarketReport {
+    let config = MarketConfig::default();
+    let secrets = parse_secrets(input);
+    let (index, bananas) = score_all(&secrets, &config).best();
+    let sequence = sequence_from_index(index);
+    let buyers = secrets
+        .into_iter()
+        .map(|s| {
+            let price = sell_price(&mut Secret(s), config.horizon, sequence, config.selection);
+            (s, price)
+        })
+        .collect();
+    MarketReport {
+        bananas,
+        sequence,
+        buyers,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Encodes four price changes as a number in base 19.
+    fn sequence_index(changes: [i8; 4]) -> usize {
+        changes
+            .iter()
+            .fold(0, |acc, c| acc * 19 + (*c + 9) as usize)
+    }
+
     const EXAMPLE: &str = "1
 10
 100
@@ -92,7 +353,7 @@ mod tests {
 
     #[test]
     fn test_secret() {
-        let mut sec = Secret(123).into_iter();
+        let mut sec = Secret(123);
         sec.next();
         assert_eq!(sec.next(), Some(15887950));
         assert_eq!(sec.next(), Some(16495136));
@@ -106,13 +367,140 @@ mod tests {
         assert_eq!(sec.next(), Some(5908254));
     }
 
+    #[test]
+    fn test_jump() {
+        let table = JumpTable::new();
+        assert_eq!(table.jump(123, 0), 123);
+        assert_eq!(table.jump(123, 1), 15887950);
+        assert_eq!(table.jump(123, 10), 5908254);
+        for (secret, n) in [(1, 2000), (10, 1234), (2024, 99999), (16777215, 7)] {
+            assert_eq!(
+                table.jump(secret, n),
+                Secret(secret).nth(n as usize).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_back() {
+        let mut sec = Secret(5908254);
+        assert_eq!(sec.back().0, 7753432);
+        assert_eq!(sec.back().0, 12249484);
+        for secret in [0, 1, 123, 2024, 16777215, 8685429] {
+            let mut sec = Secret(secret);
+            sec.next();
+            assert_eq!(sec.back().0, secret);
+        }
+        assert_eq!(rewind_secret(JumpTable::new().jump(2024, 2000), 2000), 2024);
+    }
+
     #[test]
     fn test_process_part1() {
         assert_eq!(process_part1(EXAMPLE), 37327623);
     }
 
+    #[test]
+    fn test_score() {
+        let mut scoring = Scoring::new();
+        score(&mut Secret(123), 10, Selection::First, &mut scoring);
+        assert_eq!(scoring.bananas[sequence_index([-1, -1, 0, 2])], 6);
+        assert_eq!(scoring.bananas[sequence_index([-3, 6, -1, -1])], 4);
+        assert_eq!(scoring.best(), (sequence_index([-1, -1, 0, 2]), 6));
+        // Bananas accumulate over buyers.
+        score(&mut Secret(123), 10, Selection::First, &mut scoring);
+        assert_eq!(scoring.bananas[sequence_index([-1, -1, 0, 2])], 12);
+        // Within one buyer only the first occurrence counts: the prices of 123 follow
+        // the changes 2,-2,3,-3 at index 31 with price 4 and again at index 46 with price 0.
+        let mut scoring = Scoring::new();
+        score(&mut Secret(123), 50, Selection::First, &mut scoring);
+        assert_eq!(scoring.bananas[sequence_index([2, -2, 3, -3])], 4);
+        assert_eq!(sequence_index([-9, -9, -9, -9]), 0);
+        assert_eq!(sequence_index([9, 9, 9, 9]), SEQUENCES - 1);
+    }
+
+    #[test]
+    fn test_sequence_from_index() {
+        for sequence in [
+            [-9, -9, -9, -9],
+            [9, 9, 9, 9],
+            [-2, 1, -1, 3],
+            [0, 5, -7, 1],
+        ] {
+            assert_eq!(sequence_from_index(sequence_index(sequence)), sequence);
+        }
+    }
+
     #[test]
     fn test_process_part2() {
-        assert_eq!(process_part2(EXAMPLE2), 23);
+        assert_eq!(
+            process_part2(EXAMPLE2),
+            MarketReport {
+                bananas: 23,
+                sequence: [-2, 1, -1, 3],
+                buyers: vec![(1, Some(7)), (2, Some(7)), (3, None), (2024, Some(9))]
+            }
+        );
+    }
+
+    #[test]
+    fn test_sequence_bananas() {
+        let config = MarketConfig::default();
+        assert_eq!(sequence_bananas(EXAMPLE2, [-2, 1, -1, 3], &config), 23);
+        assert_eq!(sequence_bananas(EXAMPLE2, [9, 9, 9, 9], &config), 0);
+    }
+
+    #[test]
+    fn test_top_sequences() {
+        let config = MarketConfig::default();
+        assert_eq!(
+            top_sequences(EXAMPLE2, 1, &config),
+            vec![([-2, 1, -1, 3], 23)]
+        );
+        assert_eq!(top_sequences(EXAMPLE2, 0, &config), vec![]);
+        // Every selection rule and horizon agrees with querying the sequences one by one.
+        for selection in [Selection::First, Selection::Last, Selection::Highest] {
+            for horizon in [10, 100, 2000] {
+                let config = MarketConfig { horizon, selection };
+                let top = top_sequences(EXAMPLE2, 5, &config);
+                assert!(top.len() >= 5);
+                assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+                // Ties with the fifth place are included.
+                assert!(top[4..].iter().all(|(_, b)| *b == top[4].1));
+                for (sequence, bananas) in top {
+                    assert_eq!(sequence_bananas(EXAMPLE2, sequence, &config), bananas);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut first = Scoring::new();
+        let mut last = Scoring::new();
+        let mut highest = Scoring::new();
+        score(&mut Secret(123), 10, Selection::First, &mut first);
+        score(&mut Secret(123), 10, Selection::Last, &mut last);
+        score(&mut Secret(123), 10, Selection::Highest, &mut highest);
+        assert!((0..SEQUENCES).all(|i| first.bananas[i] <= highest.bananas[i]));
+        assert!((0..SEQUENCES).all(|i| last.bananas[i] <= highest.bananas[i]));
+
+        // The changes 2,-2,3,-3 end at prices 4 and then 0,
+        // the changes 0,-4,4,-2 at prices 3, 3 and then 7.
+        let totals = |selection: Selection| {
+            let mut scoring = Scoring::new();
+            score(&mut Secret(123), 50, selection, &mut scoring);
+            let falling = scoring.bananas[sequence_index([2, -2, 3, -3])];
+            let mut scoring = Scoring::new();
+            score(&mut Secret(123), 800, selection, &mut scoring);
+            (falling, scoring.bananas[sequence_index([0, -4, 4, -2])])
+        };
+        assert_eq!(totals(Selection::First), (4, 3));
+        assert_eq!(totals(Selection::Last), (0, 7));
+        assert_eq!(totals(Selection::Highest), (4, 7));
+        let config = MarketConfig {
+            horizon: 50,
+            selection: Selection::Last,
+        };
+        assert_eq!(sequence_bananas("123", [2, -2, 3, -3], &config), 0);
     }
 }
@@ -0,0 +1,8 @@
+use day_23::process_maximal_cliques;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    for clique in process_maximal_cliques(input) {
+        println!("{}", clique);
+    }
+}
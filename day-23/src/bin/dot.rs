@@ -0,0 +1,6 @@
+use day_23::process_dot;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    print!("{}", process_dot(input));
+}
@@ -0,0 +1,6 @@
+use day_23::process_report;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    print!("{}", process_report(input));
+}
@@ -1,123 +1,406 @@
-use std::{char, collections::HashSet};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    ops::Range,
+};
 
 use itertools::Itertools;
 
-const T: u8 = b't' - b'a';
-type PC = (u8, u8);
+/// Index of an interned computer name.
+type PC = usize;
 
-struct AdjMatrix([[bool; 26 * 26]; 26 * 26]);
+/// Symmetric adjacency of `n` computers with one bit per pair.
+struct AdjMatrix {
+    n: usize,
+    /// Words per row.
+    words: usize,
+    bits: Vec<u64>,
+}
 impl AdjMatrix {
-    fn new() -> Self {
-        AdjMatrix([[false; 26 * 26]; 26 * 26])
+    fn new(n: usize) -> Self {
+        let words = n.div_ceil(64);
+        AdjMatrix {
+            n,
+            words,
+            bits: vec![0; n * words],
+        }
+    }
+
+    #[inline]
+    fn are_neigh(&self, pc1: PC, pc2: PC) -> bool {
+        self.bits[pc1 * self.words + pc2 / 64] >> (pc2 % 64) & 1 == 1
     }
 
-    fn are_neigh(&self, pc1: &PC, pc2: &PC) -> bool {
-        self.0[pc_to_cord(pc1)][pc_to_cord(pc2)]
+    fn set_neigh(&mut self, pc1: PC, pc2: PC) {
+        self.bits[pc1 * self.words + pc2 / 64] |= 1 << (pc2 % 64);
+        self.bits[pc2 * self.words + pc1 / 64] |= 1 << (pc1 % 64);
     }
 
-    fn set_neigh(&mut self, pc1: &PC, pc2: &PC) {
-        self.0[pc_to_cord(pc1)][pc_to_cord(pc2)] = true;
-        self.0[pc_to_cord(pc2)][pc_to_cord(pc1)] = true;
+    fn pcs(&self) -> Range<PC> {
+        0..self.n
     }
 
-    fn are_neigh_cord(&self, cord1: usize, cord2: usize) -> bool {
-        self.0[cord1][cord2]
+    fn row(&self, pc: PC) -> &[u64] {
+        &self.bits[pc * self.words..(pc + 1) * self.words]
+    }
+
+    fn neighbours(&self, pc: PC) -> impl Iterator<Item = PC> + '_ {
+        self.pcs().filter(move |other| self.are_neigh(pc, *other))
+    }
+
+    fn degree(&self, pc: PC) -> usize {
+        self.row(pc).iter().map(|w| w.count_ones() as usize).sum()
     }
 }
 
-#[inline]
-fn pc_to_cord(pc: &PC) -> usize {
-    (pc.0 as u16 * 26 + pc.1 as u16) as usize
+/// The LAN with computer names interned in order of first appearance.
+struct Network {
+    names: Vec<String>,
+    adj: AdjMatrix,
 }
+impl Network {
+    fn pc_to_str(&self, pc: PC) -> &str {
+        &self.names[pc]
+    }
 
-fn pc_to_str(pc: &PC) -> String {
-    ((pc.0 + b'a') as char).to_string() + &((pc.1 + b'a') as char).to_string()
+    #[inline]
+    fn starts_with_t(&self, pc: PC) -> bool {
+        self.names[pc].starts_with('t')
+    }
 }
 
-fn cord_to_pc(cord: usize) -> PC {
-    ((cord / 26) as u8, (cord % 26) as u8)
+fn parse_input<'a>(input: &'a str) -> Network {
+    let mut names: Vec<String> = Vec::new();
+    let mut ids: HashMap<&'a str, PC> = HashMap::new();
+    let mut intern = |name: &'a str| -> PC {
+        *ids.entry(name).or_insert_with(|| {
+            names.push(name.to_string());
+            names.len() - 1
+        })
+    };
+    let edges: Vec<(PC, PC)> = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let (a, b) = l.split_once('-').unwrap();
+            (intern(a.trim()), intern(b.trim()))
+        })
+        .collect();
+    let mut adj = AdjMatrix::new(names.len());
+    edges.into_iter().for_each(|(a, b)| adj.set_neigh(a, b));
+    Network { names, adj }
 }
 
-#[inline]
-fn char_to_num(c: char) -> u8 {
-    c as u8 - b'a'
+/// Set bits of a bitset as computer indices.
+fn bit_members(bits: &[u64]) -> impl Iterator<Item = PC> + '_ {
+    bits.iter().enumerate().flat_map(|(i, word)| {
+        (0..64)
+            .filter(move |b| word >> b & 1 == 1)
+            .map(move |b| i * 64 + b)
+    })
 }
 
-#[inline]
-fn starts_with_t(pc: &PC) -> bool {
-    pc.0 == T
+/// Extends `clique` to all cliques of size `k` using only the `candidates`,
+/// which are the common neighbours of the clique with a larger index than its last member.
+fn extend_cliques(
+    adj: &AdjMatrix,
+    k: usize,
+    clique: &mut Vec<PC>,
+    candidates: &[u64],
+    report: &mut impl FnMut(&[PC]),
+) {
+    if clique.len() == k {
+        report(clique);
+        return;
+    }
+    bit_members(candidates).for_each(|pc| {
+        let mut next: Vec<u64> = candidates
+            .iter()
+            .zip(adj.row(pc))
+            .map(|(c, r)| c & r)
+            .collect();
+        // Only keep larger indices so each clique is found once.
+        next.iter_mut().enumerate().for_each(|(i, word)| {
+            if (i + 1) * 64 <= pc + 1 {
+                *word = 0;
+            } else if i * 64 <= pc {
+                *word &= !0 << (pc % 64) << 1;
+            }
+        });
+        clique.push(pc);
+        extend_cliques(adj, k, clique, &next, report);
+        clique.pop();
+    });
 }
 
-fn parse_input(input: &str) -> (AdjMatrix, Vec<(u8, u8)>) {
-    let mut adj: AdjMatrix = AdjMatrix::new();
-    let mut pcs: HashSet<(u8, u8)> = HashSet::new();
-    input.lines().for_each(|l| {
-        let mut it = l.chars();
-        let a = (
-            char_to_num(it.next().unwrap()),
-            char_to_num(it.next().unwrap()),
-        );
-        it.next();
-        let b = (
-            char_to_num(it.next().unwrap()),
-            char_to_num(it.next().unwrap()),
-        );
-        adj.set_neigh(&a, &b);
-        pcs.insert(a);
-        pcs.insert(b);
+/// All cliques of size `k` with at least one member satisfying `predicate`, each sorted by index.
+fn k_cliques(adj: &AdjMatrix, k: usize, predicate: impl Fn(PC) -> bool) -> Vec<Vec<PC>> {
+    let mut cliques = Vec::new();
+    let all: Vec<u64> = (0..adj.words)
+        .map(|i| match adj.n - i * 64 {
+            rest if rest >= 64 => !0,
+            rest => (1 << rest) - 1,
+        })
+        .collect();
+    extend_cliques(adj, k, &mut Vec::new(), &all, &mut |clique| {
+        if clique.iter().any(|pc| predicate(*pc)) {
+            cliques.push(clique.to_vec());
+        }
     });
-    assert!(pcs.iter().all(|pc| pc.0 <= 25 && pc.1 <= 25));
-    (adj, pcs.into_iter().collect::<Vec<(u8, u8)>>())
+    cliques
 }
 
 pub fn process_part1(input: &str) -> u64 {
-    let (adj, pcs) = parse_input(input);
-    pcs.iter()
-        .tuple_combinations::<(&PC, &PC, &PC)>()
-        .filter(|(pc1, pc2, pc3)| {
-            (starts_with_t(pc1) || starts_with_t(pc2) || starts_with_t(pc3))
-                && adj.are_neigh(pc1, pc2)
-                && adj.are_neigh(pc1, pc3)
-                && adj.are_neigh(pc2, pc3)
-        })
-        .count() as u64
+    let network = parse_input(input);
+    k_cliques(&network.adj, 3, |pc| network.starts_with_t(pc)).len() as u64
 }
 
-pub fn process_part2(input: &str) -> String {
-    let mut cliques: HashSet<Vec<usize>> = HashSet::new();
-    let (adj, pcs) = parse_input(input);
-    pcs.into_iter()
-        .for_each(|pc| expand_cliques(&mut cliques, &adj, pc_to_cord(&pc)));
-    let mut max_clique = cliques
+/// Passwords of all `k`-cliques containing a computer whose name satisfies `predicate`.
+pub fn process_cliques(input: &str, k: usize, predicate: impl Fn(&str) -> bool) -> Vec<String> {
+    let network = parse_input(input);
+    k_cliques(&network.adj, k, |pc| predicate(network.pc_to_str(pc)))
         .iter()
-        .max_by_key(|clique| clique.len())
-        .unwrap()
-        .clone();
-    max_clique.sort();
-    max_clique
-        .into_iter()
-        .map(|cord| pc_to_str(&cord_to_pc(cord)))
+        .map(|clique| clique_to_str(&network, clique))
+        .sorted()
+        .collect()
+}
+
+/// Orders the vertices by repeatedly removing one of minimum remaining degree,
+/// so every vertex has at most degeneracy many neighbours later in the order.
+fn degeneracy_order(adj: &AdjMatrix) -> Vec<PC> {
+    let mut degree: Vec<usize> = adj.pcs().map(|pc| adj.degree(pc)).collect();
+    let mut removed = vec![false; adj.n];
+    let mut order = Vec::with_capacity(adj.n);
+    while order.len() < adj.n {
+        let pc = adj
+            .pcs()
+            .filter(|pc| !removed[*pc])
+            .min_by_key(|pc| degree[*pc])
+            .unwrap();
+        removed[pc] = true;
+        order.push(pc);
+        adj.neighbours(pc)
+            .filter(|other| !removed[*other])
+            .for_each(|other| degree[other] -= 1);
+    }
+    order
+}
+
+/// Reports every maximal clique extending `r` with vertices from `p` but none from `x`.
+fn bron_kerbosch(
+    adj: &AdjMatrix,
+    r: &mut Vec<PC>,
+    mut p: Vec<PC>,
+    mut x: Vec<PC>,
+    report: &mut impl FnMut(&[PC]),
+) {
+    if p.is_empty() {
+        if x.is_empty() {
+            report(r);
+        }
+        return;
+    }
+    // Any maximal clique contains the pivot or one of its non-neighbours.
+    let pivot = *p
+        .iter()
+        .chain(x.iter())
+        .max_by_key(|u| p.iter().filter(|v| adj.are_neigh(**u, **v)).count())
+        .unwrap();
+    let candidates: Vec<PC> = p
+        .iter()
+        .copied()
+        .filter(|v| !adj.are_neigh(pivot, *v))
+        .collect();
+    for v in candidates {
+        r.push(v);
+        bron_kerbosch(
+            adj,
+            r,
+            p.iter().copied().filter(|u| adj.are_neigh(v, *u)).collect(),
+            x.iter().copied().filter(|u| adj.are_neigh(v, *u)).collect(),
+            report,
+        );
+        r.pop();
+        p.retain(|u| *u != v);
+        x.push(v);
+    }
+}
+
+/// Runs Bron–Kerbosch once per vertex in degeneracy order, keeping the recursion shallow.
+fn for_each_maximal_clique(adj: &AdjMatrix, mut report: impl FnMut(&[PC])) {
+    let order = degeneracy_order(adj);
+    order.iter().enumerate().for_each(|(i, v)| {
+        let (before, after) = (&order[..i], &order[i + 1..]);
+        let neighbours = |vs: &[PC]| {
+            vs.iter()
+                .copied()
+                .filter(|u| adj.are_neigh(*v, *u))
+                .collect()
+        };
+        bron_kerbosch(
+            adj,
+            &mut vec![*v],
+            neighbours(after),
+            neighbours(before),
+            &mut report,
+        );
+    });
+}
+
+fn clique_to_str(network: &Network, clique: &[PC]) -> String {
+    clique
+        .iter()
+        .map(|pc| network.pc_to_str(*pc))
+        .sorted()
         .join(",")
 }
 
-fn expand_cliques(cliques: &mut HashSet<Vec<usize>>, adj: &AdjMatrix, v: usize) {
-    let mut new_cliques: Vec<Vec<usize>> = cliques
+fn max_clique(adj: &AdjMatrix) -> Vec<PC> {
+    let mut max_clique: Vec<PC> = Vec::new();
+    for_each_maximal_clique(adj, |clique| {
+        if clique.len() > max_clique.len() {
+            max_clique = clique.to_vec();
+        }
+    });
+    max_clique
+}
+
+pub fn process_part2(input: &str) -> String {
+    let network = parse_input(input);
+    clique_to_str(&network, &max_clique(&network.adj))
+}
+
+/// All maximal cliques as sorted passwords, largest first.
+pub fn process_maximal_cliques(input: &str) -> Vec<String> {
+    let network = parse_input(input);
+    let mut cliques: Vec<Vec<PC>> = Vec::new();
+    for_each_maximal_clique(&network.adj, |clique| cliques.push(clique.to_vec()));
+    cliques.sort_by_key(|clique| Reverse(clique.len()));
+    cliques
         .iter()
-        .filter(|clique| clique.iter().all(|pc| adj.are_neigh_cord(*pc, v)))
-        .map(|clique| {
-            let mut c = clique.clone();
-            c.push(v);
-            c
+        .map(|clique| clique_to_str(&network, clique))
+        .collect()
+}
+
+/// Sizes of the connected components, largest first.
+fn components(adj: &AdjMatrix) -> Vec<usize> {
+    let mut visited = vec![false; adj.n];
+    let mut sizes: Vec<usize> = adj
+        .pcs()
+        .filter_map(|start| {
+            if visited[start] {
+                return None;
+            }
+            visited[start] = true;
+            let mut stack = vec![start];
+            let mut size = 0;
+            while let Some(pc) = stack.pop() {
+                size += 1;
+                adj.neighbours(pc).for_each(|next| {
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push(next);
+                    }
+                });
+            }
+            Some(size)
         })
         .collect();
-    new_cliques.push(vec![v]);
-    cliques.extend(new_cliques);
+    sizes.sort_by_key(|size| Reverse(*size));
+    sizes
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NetworkReport {
+    pub vertices: usize,
+    pub edges: usize,
+    /// Number of computers per degree.
+    pub degrees: BTreeMap<usize, usize>,
+    /// Sizes of the connected components, largest first.
+    pub components: Vec<usize>,
+    pub max_clique: String,
+}
+impl Display for NetworkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Computers: {}", self.vertices)?;
+        writeln!(f, "Connections: {}", self.edges)?;
+        writeln!(f, "Degrees:")?;
+        self.degrees
+            .iter()
+            .try_for_each(|(degree, count)| writeln!(f, "{:>5}: {}", degree, count))?;
+        writeln!(f, "Components: {:?}", self.components)?;
+        writeln!(f, "Maximum clique: {}", self.max_clique)
+    }
+}
+
+pub fn process_report(input: &str) -> NetworkReport {
+    let network = parse_input(input);
+    let adj = &network.adj;
+    let mut degrees = BTreeMap::new();
+    adj.pcs()
+        .for_each(|pc| *degrees.entry(adj.degree(pc)).or_insert(0) += 1);
+    NetworkReport {
+        vertices: adj.n,
+        edges: adj.pcs().map(|pc| adj.degree(pc)).sum::<usize>() / 2,
+        degrees,
+        components: components(adj),
+        max_clique: clique_to_str(&network, &max_clique(adj)),
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The network in Graphviz DOT format with the maximum clique highlighted.
+pub fn process_dot(input: &str) -> String {
+    let network = parse_input(input);
+    let adj = &network.adj;
+    let clique = max_clique(adj);
+    let mut dot = String::from("graph lan {\n");
+    adj.pcs().for_each(|pc| {
+        let style = if clique.contains(&pc) {
+            " [style=filled, fillcolor=red]"
+        } else {
+            ""
+        };
+        dot += &format!("    {}{};\n", dot_id(network.pc_to_str(pc)), style);
+    });
+    adj.pcs()
+        .flat_map(|pc1| {
+            adj.neighbours(pc1)
+                .filter(move |pc2| pc1 < *pc2)
+                .map(move |pc2| (pc1, pc2))
+        })
+        .for_each(|(pc1, pc2)| {
+            let style = if clique.contains(&pc1) && clique.contains(&pc2) {
+                " [color=red, penwidth=2]"
+            } else {
+                ""
+            };
+            dot += &format!(
+                "    {} -- {}{};\n",
+                dot_id(network.pc_to_str(pc1)),
+                dot_id(network.pc_to_str(pc2)),
+                style
+            );
+        });
+    dot += "}\n";
+    dot
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
 
+    fn str_to_pc(network: &Network, name: &str) -> Option<PC> {
+        network.names.iter().position(|n| n == name)
+    }
+
     const EXAMPLE: &str = "kh-tc
 qp-kh
 de-cg
@@ -152,15 +435,29 @@ tb-vc
 td-yn";
 
     #[test]
-    fn pc_to_cord_bijection() {
-        let mut arr = [false; 26 * 26];
-        (0..26_u8)
-            .cartesian_product(0..26_u8)
-            .map(|pc| pc_to_cord(&pc))
-            .inspect(|a| println!("{}", a))
-            .for_each(|cord| arr[cord] = true);
-        println!("{:?}", arr);
-        assert!(arr.iter().all(|b| *b));
+    fn test_parse_input() {
+        let network = parse_input("alpha-b\nb-tc3\n\n  gamma-alpha  \n");
+        assert_eq!(network.names, vec!["alpha", "b", "tc3", "gamma"]);
+        assert_eq!(str_to_pc(&network, "gamma"), Some(3));
+        assert_eq!(str_to_pc(&network, "delta"), None);
+        assert!(network.adj.are_neigh(0, 1) && network.adj.are_neigh(1, 0));
+        assert!(network.adj.are_neigh(3, 0) && !network.adj.are_neigh(3, 1));
+        assert_eq!(network.adj.neighbours(0).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(network.adj.degree(2), 1);
+        assert!(network.starts_with_t(2) && !network.starts_with_t(0));
+    }
+
+    #[test]
+    fn test_large_network() {
+        // More than 64 computers with long names spread rows over several words.
+        let input = (0..100)
+            .map(|i| format!("node{}-node{}", i, (i + 1) % 100))
+            .join("\n");
+        let network = parse_input(&input);
+        assert_eq!(network.names.len(), 100);
+        assert!(network.adj.pcs().all(|pc| network.adj.degree(pc) == 2));
+        assert!(network.adj.are_neigh(99, 0));
+        assert_eq!(process_maximal_cliques(&input).len(), 100);
     }
 
     #[test]
@@ -172,4 +469,100 @@ td-yn";
     fn test_process_part2() {
         assert_eq!(process_part2(EXAMPLE), "co,de,ka,ta");
     }
+
+    #[test]
+    fn test_maximal_cliques() {
+        let cliques = process_maximal_cliques(EXAMPLE);
+        assert_eq!(cliques[0], "co,de,ka,ta");
+        let network = parse_input(EXAMPLE);
+        let adj = &network.adj;
+        let mut seen = HashSet::new();
+        for clique in &cliques {
+            assert!(seen.insert(clique.clone()));
+            let members: Vec<PC> = clique
+                .split(',')
+                .map(|s| str_to_pc(&network, s).unwrap())
+                .collect();
+            assert!(members
+                .iter()
+                .tuple_combinations()
+                .all(|(a, b)| adj.are_neigh(*a, *b)));
+            assert!(!network
+                .adj
+                .pcs()
+                .any(|pc| !members.contains(&pc) && members.iter().all(|m| adj.are_neigh(*m, pc))));
+        }
+        // Every edge lies in some maximal clique.
+        assert!(network.adj.pcs().tuple_combinations().all(|(a, b)| {
+            !adj.are_neigh(a, b)
+                || cliques
+                    .iter()
+                    .any(|c| c.contains(network.pc_to_str(a)) && c.contains(network.pc_to_str(b)))
+        }));
+    }
+
+    #[test]
+    fn test_process_cliques() {
+        assert_eq!(
+            process_cliques(EXAMPLE, 3, |name| name.starts_with('t')),
+            vec![
+                "co,de,ta", "co,ka,ta", "de,ka,ta", "qp,td,wh", "tb,vc,wq", "tc,td,wh", "td,wh,yn"
+            ]
+        );
+        assert_eq!(process_cliques(EXAMPLE, 3, |_| true).len(), 12);
+        assert_eq!(process_cliques(EXAMPLE, 4, |_| true), vec!["co,de,ka,ta"]);
+        assert_eq!(
+            process_cliques(EXAMPLE, 4, |name| name == "wh"),
+            Vec::<String>::new()
+        );
+        assert_eq!(process_cliques(EXAMPLE, 1, |name| name == "wh"), vec!["wh"]);
+        assert_eq!(
+            process_cliques(EXAMPLE, 2, |_| true).len(),
+            EXAMPLE.lines().count()
+        );
+    }
+
+    #[test]
+    fn test_k_cliques_across_words() {
+        // A complete graph on 70 computers spans two words per row.
+        let input = (0..70)
+            .tuple_combinations()
+            .map(|(a, b)| format!("n{}-n{}", a, b))
+            .join("\n");
+        let network = parse_input(&input);
+        assert_eq!(k_cliques(&network.adj, 2, |_| true).len(), 70 * 69 / 2);
+        assert_eq!(k_cliques(&network.adj, 3, |_| true).len(), 70 * 69 * 68 / 6);
+    }
+
+    #[test]
+    fn test_process_report() {
+        let report = process_report(EXAMPLE);
+        assert_eq!(report.vertices, 16);
+        assert_eq!(report.edges, 32);
+        assert_eq!(report.degrees, BTreeMap::from([(4, 16)]));
+        assert_eq!(report.components, vec![16]);
+        assert_eq!(report.max_clique, "co,de,ka,ta");
+        let report = process_report("a-b\nb-c\nd-e\nf-g\ng-h\nh-f\nf-i");
+        assert_eq!(report.degrees, BTreeMap::from([(1, 5), (2, 3), (3, 1)]));
+        assert_eq!(report.components, vec![4, 3, 2]);
+        assert_eq!(report.max_clique, "f,g,h");
+    }
+
+    #[test]
+    fn test_process_dot() {
+        assert_eq!(
+            process_dot("a-b\nb-c\nc-a\nc-\"d\""),
+            r#"graph lan {
+    "a" [style=filled, fillcolor=red];
+    "b" [style=filled, fillcolor=red];
+    "c" [style=filled, fillcolor=red];
+    "\"d\"";
+    "a" -- "b" [color=red, penwidth=2];
+    "a" -- "c" [color=red, penwidth=2];
+    "b" -- "c" [color=red, penwidth=2];
+    "c" -- "\"d\"";
+}
+"#
+        );
+    }
 }
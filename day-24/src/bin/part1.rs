@@ -3,5 +3,8 @@ use day_24::process_part1;
 fn main() {
     let input = include_str!("../../input1.txt");
     let output = process_part1(input);
-    println!("Output: {:?}", output);
+    match output {
+        Ok(z) => println!("Output: {}", z),
+        Err(e) => eprintln!("Error: {}", e),
+    }
 }
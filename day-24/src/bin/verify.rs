@@ -0,0 +1,9 @@
+use day_24::process_verify;
+
+fn main() {
+    let input = include_str!("../../input1.txt");
+    match process_verify(input) {
+        Ok(verification) => println!("{}", verification),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
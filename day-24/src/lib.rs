@@ -1,34 +1,50 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
 
 use itertools::Itertools;
 
 type VarMap = HashMap<String, i32>;
 
 enum Operation {
-    OR(i32, i32, i32),
-    XOR(i32, i32, i32),
-    AND(i32, i32, i32),
+    Or(i32, i32, i32),
+    Xor(i32, i32, i32),
+    And(i32, i32, i32),
+    Nand(i32, i32, i32),
+    Nor(i32, i32, i32),
+    Xnor(i32, i32, i32),
+    Not(i32, i32),
+    /// An initial value or a constant gate.
     Set(i32, bool),
 }
 impl Operation {
     fn add(&self, sat: &mut cat_solver::Solver) {
         match self {
-            Operation::OR(l1, l2, out) => {
+            Operation::Or(l1, l2, out) => {
                 sat.add_clause(vec![-*l1, *out]);
                 sat.add_clause(vec![-*l2, *out]);
                 sat.add_clause(vec![*l1, *l2, -*out]);
             }
-            Operation::XOR(l1, l2, out) => {
+            Operation::Xor(l1, l2, out) => {
                 sat.add_clause(vec![-*l1, *l2, *out]);
                 sat.add_clause(vec![*l1, -*l2, *out]);
                 sat.add_clause(vec![-*l1, -*l2, -*out]);
                 sat.add_clause(vec![*l1, *l2, -*out]);
             }
-            Operation::AND(l1, l2, out) => {
+            Operation::And(l1, l2, out) => {
                 sat.add_clause(vec![-l1, -l2, *out]);
                 sat.add_clause(vec![*l1, -*out]);
                 sat.add_clause(vec![*l2, -*out]);
             }
+            // The negated gates are encoded with the negated output literal.
+            Operation::Nand(l1, l2, out) => Operation::And(*l1, *l2, -*out).add(sat),
+            Operation::Nor(l1, l2, out) => Operation::Or(*l1, *l2, -*out).add(sat),
+            Operation::Xnor(l1, l2, out) => Operation::Xor(*l1, *l2, -*out).add(sat),
+            Operation::Not(l, out) => {
+                sat.add_clause(vec![*l, *out]);
+                sat.add_clause(vec![-*l, -*out]);
+            }
             Operation::Set(out, b) => {
                 if *b {
                     sat.add_clause(vec![*out]);
@@ -38,71 +54,425 @@ impl Operation {
             }
         }
     }
+
+    fn inputs(&self) -> Vec<i32> {
+        match self {
+            Operation::Or(l1, l2, _)
+            | Operation::Xor(l1, l2, _)
+            | Operation::And(l1, l2, _)
+            | Operation::Nand(l1, l2, _)
+            | Operation::Nor(l1, l2, _)
+            | Operation::Xnor(l1, l2, _) => vec![*l1, *l2],
+            Operation::Not(l, _) => vec![*l],
+            Operation::Set(_, _) => vec![],
+        }
+    }
+
+    fn output(&self) -> i32 {
+        match self {
+            Operation::Or(_, _, out)
+            | Operation::Xor(_, _, out)
+            | Operation::And(_, _, out)
+            | Operation::Nand(_, _, out)
+            | Operation::Nor(_, _, out)
+            | Operation::Xnor(_, _, out)
+            | Operation::Not(_, out)
+            | Operation::Set(out, _) => *out,
+        }
+    }
+
+    /// The output value given the values of all inputs.
+    fn eval(&self, values: &[Option<bool>]) -> bool {
+        let v = |l: &i32| values[*l as usize].unwrap();
+        match self {
+            Operation::Or(l1, l2, _) => v(l1) | v(l2),
+            Operation::Xor(l1, l2, _) => v(l1) ^ v(l2),
+            Operation::And(l1, l2, _) => v(l1) & v(l2),
+            Operation::Nand(l1, l2, _) => !(v(l1) & v(l2)),
+            Operation::Nor(l1, l2, _) => !(v(l1) | v(l2)),
+            Operation::Xnor(l1, l2, _) => !(v(l1) ^ v(l2)),
+            Operation::Not(l, _) => !v(l),
+            Operation::Set(_, b) => *b,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// An initial value other than `0` or `1`.
+    InvalidValue {
+        line: usize,
+        value: String,
+    },
+    UnknownGate {
+        line: usize,
+        gate: String,
+    },
+    Malformed {
+        line: usize,
+    },
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidValue { line, value } => {
+                write!(f, "line {}: invalid wire value {}", line, value)
+            }
+            ParseError::UnknownGate { line, gate } => {
+                write!(f, "line {}: unknown gate {}", line, gate)
+            }
+            ParseError::Malformed { line } => write!(f, "line {}: malformed", line),
+        }
+    }
 }
 
-fn parse_input(input: &str) -> (VarMap, Vec<Operation>) {
-    let mut lines = input.lines();
+fn parse_bit(line: usize, value: &str) -> Result<bool, ParseError> {
+    match value {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(ParseError::InvalidValue {
+            line,
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Parses initial values `w: 0`, gates `a OP b -> w` for AND, OR, XOR, NAND, NOR and XNOR,
+/// negations `NOT a -> w` and constants `1 -> w`.
+fn parse_circuit(input: &str) -> Result<(VarMap, Vec<Operation>), ParseError> {
     let mut var_map: VarMap = HashMap::new();
     let mut operations: Vec<Operation> = vec![];
-    let mut i = 1;
-    loop {
-        let l = lines.next().unwrap();
+    let mut lit = |name: &str| -> i32 {
+        let next = var_map.len() as i32 + 1;
+        *var_map.entry(name.to_string()).or_insert(next)
+    };
+    let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    for (line, l) in lines.by_ref() {
         if l.is_empty() {
             break;
         }
-        let (lit, val) = l.split_once(": ").unwrap();
-        var_map.insert(lit.to_string(), i);
-        operations.push(Operation::Set(i, val.parse::<u8>().unwrap() == 1));
-        i += 1;
-    }
-    while let Some(l) = lines.next() {
-        let (front, out_str) = l.split_once(" -> ").unwrap();
-        if !var_map.contains_key(out_str) {
-            var_map.insert(out_str.to_string(), i);
-            i += 1;
-        }
-        let mut front_it = front.split_whitespace();
-        let lit1 = front_it.next().unwrap();
-        if !var_map.contains_key(lit1) {
-            var_map.insert(lit1.to_string(), i);
-            i += 1;
+        let (name, val) = l.split_once(':').ok_or(ParseError::Malformed { line })?;
+        operations.push(Operation::Set(
+            lit(name.trim()),
+            parse_bit(line, val.trim())?,
+        ));
+    }
+    for (line, l) in lines.filter(|(_, l)| !l.is_empty()) {
+        let (front, out_str) = l.split_once("->").ok_or(ParseError::Malformed { line })?;
+        let out = lit(out_str.trim());
+        let operation = match front.split_whitespace().collect::<Vec<_>>()[..] {
+            [value] => Operation::Set(out, parse_bit(line, value)?),
+            ["NOT", l] => Operation::Not(lit(l), out),
+            [l1, op, l2] => {
+                let (l1, l2) = (lit(l1), lit(l2));
+                match op {
+                    "OR" => Operation::Or(l1, l2, out),
+                    "AND" => Operation::And(l1, l2, out),
+                    "XOR" => Operation::Xor(l1, l2, out),
+                    "NAND" => Operation::Nand(l1, l2, out),
+                    "NOR" => Operation::Nor(l1, l2, out),
+                    "XNOR" => Operation::Xnor(l1, l2, out),
+                    _ => {
+                        return Err(ParseError::UnknownGate {
+                            line,
+                            gate: op.to_string(),
+                        })
+                    }
+                }
+            }
+            _ => return Err(ParseError::Malformed { line }),
+        };
+        operations.push(operation);
+    }
+    Ok((var_map, operations))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EvalError {
+    /// A wire is read by a gate but neither set initially nor driven by any gate.
+    Undriven { wire: String },
+    /// A wire is driven by more than one gate or initial value.
+    MultipleDrivers { wire: String },
+    /// These wires could not be evaluated because of a cycle,
+    /// either lying on it or reading from it.
+    Cycle { wires: Vec<String> },
+}
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::Undriven { wire } => write!(f, "wire {} is never driven", wire),
+            EvalError::MultipleDrivers { wire } => write!(f, "wire {} has multiple drivers", wire),
+            EvalError::Cycle { wires } => write!(
+                f,
+                "wires {} could not be evaluated because of a cycle",
+                wires.join(",")
+            ),
         }
-        let op = front_it.next().unwrap();
-        let lit2 = front_it.next().unwrap();
-        if !var_map.contains_key(lit2) {
-            var_map.insert(lit2.to_string(), i);
-            i += 1;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CircuitError {
+    Parse(ParseError),
+    Eval(EvalError),
+    /// A bus has a higher bit than this wire, but not this one.
+    MissingBusWire {
+        wire: String,
+    },
+}
+impl Display for CircuitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitError::Parse(e) => e.fmt(f),
+            CircuitError::Eval(e) => e.fmt(f),
+            CircuitError::MissingBusWire { wire } => write!(f, "bus wire {} is missing", wire),
         }
+    }
+}
+impl From<ParseError> for CircuitError {
+    fn from(e: ParseError) -> Self {
+        CircuitError::Parse(e)
+    }
+}
+impl From<EvalError> for CircuitError {
+    fn from(e: EvalError) -> Self {
+        CircuitError::Eval(e)
+    }
+}
 
-        let l1 = var_map.get(lit1).unwrap();
-        let l2 = var_map.get(lit2).unwrap();
-        let lout = var_map.get(out_str).unwrap();
+/// Wire names indexed by literal.
+fn wire_names(var_map: &VarMap) -> Vec<String> {
+    let mut names = vec![String::new(); var_map.len() + 1];
+    var_map
+        .iter()
+        .for_each(|(name, lit)| names[*lit as usize] = name.clone());
+    names
+}
+
+/// Orders the gates such that every gate comes after the gates driving its inputs.
+/// Fails unless every wire has exactly one driver and the gates are free of cycles.
+fn topological_order(var_map: &VarMap, operations: &[Operation]) -> Result<Vec<usize>, EvalError> {
+    let names = wire_names(var_map);
+    let mut driven = vec![false; names.len()];
+    // Gates reading each wire.
+    let mut readers: Vec<Vec<usize>> = vec![vec![]; names.len()];
+    let mut missing: Vec<usize> = operations.iter().map(|op| op.inputs().len()).collect();
+    for (i, op) in operations.iter().enumerate() {
+        let out = op.output() as usize;
+        if driven[out] {
+            return Err(EvalError::MultipleDrivers {
+                wire: names[out].clone(),
+            });
+        }
+        driven[out] = true;
+        op.inputs()
+            .iter()
+            .for_each(|l| readers[*l as usize].push(i));
+    }
+    if let Some(l) = (1..names.len()).find(|l| !driven[*l]) {
+        return Err(EvalError::Undriven {
+            wire: names[l].clone(),
+        });
+    }
 
-        operations.push(match op {
-            "OR" => Operation::OR(*l1, *l2, *lout),
-            "AND" => Operation::AND(*l1, *l2, *lout),
-            "XOR" => Operation::XOR(*l1, *l2, *lout),
-            _ => unreachable!(),
+    let mut order: Vec<usize> = vec![];
+    let mut ready: VecDeque<usize> = (0..operations.len()).filter(|i| missing[*i] == 0).collect();
+    while let Some(i) = ready.pop_front() {
+        order.push(i);
+        let out = operations[i].output() as usize;
+        readers[out].iter().for_each(|r| {
+            missing[*r] -= 1;
+            if missing[*r] == 0 {
+                ready.push_back(*r);
+            }
         });
     }
-    (var_map, operations)
+    if order.len() < operations.len() {
+        let wires = (0..operations.len())
+            .filter(|i| missing[*i] > 0)
+            .map(|i| names[operations[i].output() as usize].clone())
+            .sorted()
+            .collect();
+        return Err(EvalError::Cycle { wires });
+    }
+    Ok(order)
 }
 
-pub fn process_part1(input: &str) -> u64 {
-    let (var_map, operations) = parse_input(input);
+/// Evaluates the gates in topological order, returning the value of every wire by literal.
+fn evaluate(var_map: &VarMap, operations: &[Operation]) -> Result<Vec<Option<bool>>, EvalError> {
+    let mut values: Vec<Option<bool>> = vec![None; var_map.len() + 1];
+    for i in topological_order(var_map, operations)? {
+        values[operations[i].output() as usize] = Some(operations[i].eval(&values));
+    }
+    Ok(values)
+}
+
+/// Decodes a bus as a binary number, the wire ending in 00 being the least significant bit.
+fn decode(lits: &[i32], value: impl Fn(i32) -> bool) -> u64 {
+    lits.iter()
+        .rev()
+        .fold(0_u64, |acc, lit| (acc * 2) + value(*lit) as u64)
+}
+
+/// Evaluates the circuit and decodes the output bus `{prefix}00`, `{prefix}01`, ...
+pub fn process_bus(input: &str, prefix: &str) -> Result<u64, CircuitError> {
+    let (var_map, operations) = parse_circuit(input)?;
+    let values = evaluate(&var_map, &operations)?;
+    Ok(decode(&bus(&var_map, prefix)?, |lit| {
+        values[lit as usize].unwrap()
+    }))
+}
+
+pub fn process_part1(input: &str) -> Result<u64, CircuitError> {
+    process_bus(input, "z")
+}
+
+/// Solves part 1 by encoding the circuit as CNF instead, used to cross-check the evaluator.
+pub fn process_part1_sat(input: &str) -> Result<u64, CircuitError> {
+    let (var_map, operations) = parse_circuit(input)?;
+    let z = bus(&var_map, "z")?;
     let mut sat: cat_solver::Solver = cat_solver::Solver::new();
     operations.iter().for_each(|op| op.add(&mut sat));
     sat.solve();
-    var_map
+    Ok(decode(&z, |lit| sat.value(lit).unwrap()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// The circuit computes `z = x + y` for all inputs of this many bits.
+    Correct { bits: usize },
+    /// The solver gave up before deciding either way.
+    Unknown,
+    /// Inputs for which the circuit disagrees with the adder.
+    Counterexample {
+        x: u64,
+        y: u64,
+        expected: u64,
+        actual: u64,
+    },
+}
+impl Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verification::Correct { bits } => write!(f, "correct {}-bit adder", bits),
+            Verification::Unknown => write!(f, "the solver could not decide"),
+            Verification::Counterexample {
+                x,
+                y,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} + {} = {}, but the circuit computes {}",
+                x, y, expected, actual
+            ),
+        }
+    }
+}
+
+/// Literals of the wires `{prefix}00`, `{prefix}01`, ... up to the highest numbered one.
+/// Fails if a lower numbered wire is missing.
+fn bus(var_map: &VarMap, prefix: &str) -> Result<Vec<i32>, CircuitError> {
+    let width = var_map
+        .keys()
+        .filter_map(|name| name.strip_prefix(prefix)?.parse::<usize>().ok())
+        .max()
+        .map_or(0, |i| i + 1);
+    (0..width)
+        .map(|i| {
+            let wire = format!("{}{:02}", prefix, i);
+            var_map
+                .get(&wire)
+                .copied()
+                .ok_or(CircuitError::MissingBusWire { wire })
+        })
+        .collect()
+}
+
+/// Builds a miter of the circuit and a ripple carry adder over the same `x` and `y`,
+/// which is satisfiable exactly by the inputs where some bit of `z` differs.
+/// The solver gives up after `decisions` decisions, if given.
+/// The circuit has to pass the same checks as for evaluation,
+/// as undriven wires and cycles would leave wires unconstrained.
+fn verify(
+    var_map: &VarMap,
+    operations: &[Operation],
+    decisions: Option<u32>,
+) -> Result<Verification, CircuitError> {
+    topological_order(var_map, operations)?;
+    let (x, y, z) = (bus(var_map, "x")?, bus(var_map, "y")?, bus(var_map, "z")?);
+    let bits = x.len().max(y.len());
+    let mut sat: cat_solver::Solver = cat_solver::Solver::new();
+    // The inputs stay free, only the gates are encoded.
+    operations
         .iter()
-        .filter(|(k, _)| k.starts_with("z"))
-        .map(|(k, lit)| (k, sat.value(*lit).unwrap()))
-        .sorted_by_key(|(k, _)| *k)
-        .rev()
-        .fold(0_u64, |acc, (k, lit)| (acc * 2) + lit as u64)
+        .filter(|op| !matches!(op, Operation::Set(out, _) if x.contains(out) || y.contains(out)))
+        .for_each(|op| op.add(&mut sat));
+
+    let mut next = var_map.len() as i32;
+    let mut fresh = || {
+        next += 1;
+        next
+    };
+    let zero = fresh();
+    Operation::Set(zero, false).add(&mut sat);
+    let bit = |lits: &[i32], i: usize| lits.get(i).copied().unwrap_or(zero);
+    let mut carry = zero;
+    let mut sum: Vec<i32> = (0..bits)
+        .map(|i| {
+            let (half, s, both, carried, next_carry) =
+                (fresh(), fresh(), fresh(), fresh(), fresh());
+            [
+                Operation::Xor(bit(&x, i), bit(&y, i), half),
+                Operation::Xor(half, carry, s),
+                Operation::And(bit(&x, i), bit(&y, i), both),
+                Operation::And(half, carry, carried),
+                Operation::Or(both, carried, next_carry),
+            ]
+            .iter()
+            .for_each(|op| op.add(&mut sat));
+            carry = next_carry;
+            s
+        })
+        .collect();
+    sum.push(carry);
+
+    let differences: Vec<i32> = (0..sum.len().max(z.len()))
+        .map(|i| {
+            let difference = fresh();
+            Operation::Xor(bit(&sum, i), bit(&z, i), difference).add(&mut sat);
+            difference
+        })
+        .collect();
+    sat.add_clause(differences);
+
+    if let Some(limit) = decisions {
+        sat.set_limit("decisions", limit).unwrap();
+    }
+    match sat.solve() {
+        Some(false) => return Ok(Verification::Correct { bits }),
+        None => return Ok(Verification::Unknown),
+        Some(true) => (),
+    }
+    let value = |lits: &[i32]| {
+        lits.iter().rev().fold(0_u64, |acc, lit| {
+            acc * 2 + sat.value(*lit).unwrap_or(false) as u64
+        })
+    };
+    Ok(Verification::Counterexample {
+        x: value(&x),
+        y: value(&y),
+        expected: value(&sum),
+        actual: value(&z),
+    })
+}
+
+pub fn process_verify(input: &str) -> Result<Verification, CircuitError> {
+    let (var_map, operations) = parse_circuit(input)?;
+    verify(&var_map, &operations, None)
 }
 
-pub fn process_part2(input: &str) -> String {
+pub fn process_part2(_input: &str) -> String {
     todo!()
 }
 
@@ -158,12 +528,228 @@ hwm AND bqk -> z03
 tgd XOR rvg -> z12
 tnw OR pbm -> gnj";
 
+    const SMALL_EXAMPLE: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
     #[test]
     fn test_process_part1() {
-        assert_eq!(process_part1(EXAMPLE), 2024);
+        assert_eq!(process_part1(EXAMPLE), Ok(2024));
+        assert_eq!(process_part1(SMALL_EXAMPLE), Ok(4));
+        assert_eq!(process_part1_sat(EXAMPLE), Ok(2024));
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        assert_eq!(
+            process_part1("x00: 1\n\nx00 AND y00 -> z00"),
+            Err(CircuitError::Eval(EvalError::Undriven {
+                wire: "y00".to_string()
+            }))
+        );
+        assert_eq!(
+            process_part1("x00: 1\n\nx00 AND a -> b\nb OR x00 -> a\nx00 XOR x00 -> z00"),
+            Err(CircuitError::Eval(EvalError::Cycle {
+                wires: vec!["a".to_string(), "b".to_string()]
+            }))
+        );
+        // Wires reading from the cycle can not be evaluated either.
+        assert_eq!(
+            process_part1("x00: 1\n\nx00 AND a -> b\nb OR x00 -> a\na XOR x00 -> z00"),
+            Err(CircuitError::Eval(EvalError::Cycle {
+                wires: vec!["a".to_string(), "b".to_string(), "z00".to_string()]
+            }))
+        );
+        assert_eq!(
+            process_part1("x00: 1\n\nx00 AND x00 -> z00\nx00 OR x00 -> z00"),
+            Err(CircuitError::Eval(EvalError::MultipleDrivers {
+                wire: "z00".to_string()
+            }))
+        );
+        assert_eq!(
+            process_part1("x00: 1\n\nx00 AND x00 -> z00\nx00 OR x00 -> z02"),
+            Err(CircuitError::MissingBusWire {
+                wire: "z01".to_string()
+            })
+        );
+    }
+
+    /// A ripple carry adder of the given width in puzzle notation.
+    fn adder(bits: usize) -> String {
+        let inputs = (0..bits)
+            .flat_map(|i| [format!("x{:02}: 0", i), format!("y{:02}: 0", i)])
+            .join("\n");
+        let gates = (0..bits)
+            .flat_map(|i| {
+                let carry = format!("c{:02}", i);
+                let next = if i + 1 == bits {
+                    format!("z{:02}", bits)
+                } else {
+                    format!("c{:02}", i + 1)
+                };
+                if i == 0 {
+                    return vec![
+                        "x00 XOR y00 -> z00".to_string(),
+                        format!("x00 AND y00 -> {}", next),
+                    ];
+                }
+                vec![
+                    format!("x{0:02} XOR y{0:02} -> h{0:02}", i),
+                    format!("h{0:02} XOR {1} -> z{0:02}", i, carry),
+                    format!("x{0:02} AND y{0:02} -> a{0:02}", i),
+                    format!("h{0:02} AND {1} -> b{0:02}", i, carry),
+                    format!("a{0:02} OR b{0:02} -> {1}", i, next),
+                ]
+            })
+            .join("\n");
+        format!("{}\n\n{}", inputs, gates)
+    }
+
+    #[test]
+    fn test_process_verify() {
+        assert_eq!(
+            process_verify(&adder(1)),
+            Ok(Verification::Correct { bits: 1 })
+        );
+        assert_eq!(
+            process_verify(&adder(6)),
+            Ok(Verification::Correct { bits: 6 })
+        );
+        // Swapping the outputs of two gates breaks the adder.
+        let broken = adder(6)
+            .replace("-> z03", "-> tmp")
+            .replace("-> a03", "-> z03")
+            .replace("-> tmp", "-> a03");
+        let Ok(Verification::Counterexample {
+            x,
+            y,
+            expected,
+            actual,
+        }) = process_verify(&broken)
+        else {
+            panic!("swapped gates were not detected");
+        };
+        assert_eq!(expected, x + y);
+        assert_ne!(actual, expected);
+        // The counterexample reproduces with the evaluator.
+        let inputs = (0..6)
+            .flat_map(|i| {
+                [
+                    format!("x{:02}: {}", i, x >> i & 1),
+                    format!("y{:02}: {}", i, y >> i & 1),
+                ]
+            })
+            .join("\n");
+        let (_, gates) = broken.split_once("\n\n").unwrap();
+        assert_eq!(
+            process_part1(&format!("{}\n\n{}", inputs, gates)),
+            Ok(actual)
+        );
+    }
+
+    #[test]
+    fn test_verify_structure() {
+        assert_eq!(
+            process_verify(&adder(3).replace("h01 AND c01", "h01 AND q")),
+            Err(CircuitError::Eval(EvalError::Undriven {
+                wire: "q".to_string()
+            }))
+        );
+        assert_eq!(
+            process_verify(&adder(3).replace("h01 AND c01", "h01 AND b01")),
+            Err(CircuitError::Eval(EvalError::Cycle {
+                wires: vec![
+                    "b01".to_string(),
+                    "b02".to_string(),
+                    "c02".to_string(),
+                    "z02".to_string(),
+                    "z03".to_string()
+                ]
+            }))
+        );
+        assert_eq!(
+            process_verify(&adder(3).replace("-> a01", "-> c01")),
+            Err(CircuitError::Eval(EvalError::MultipleDrivers {
+                wire: "c01".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn test_verify_unknown() {
+        let (var_map, operations) = parse_circuit(&adder(6)).unwrap();
+        assert_eq!(
+            verify(&var_map, &operations, Some(0)),
+            Ok(Verification::Unknown)
+        );
+        assert_eq!(
+            Verification::Unknown.to_string(),
+            "the solver could not decide"
+        );
+    }
+
+    #[test]
+    fn test_extended_gates() {
+        let input = "a: 1
+b: 0
+
+a NAND b -> z00
+a NOR b -> z01
+a XNOR b -> z02
+NOT b -> z03
+0 -> z04
+1 -> k
+k XNOR a -> out00
+NOT k -> out01
+a AND k -> out02";
+        assert_eq!(process_part1(input), Ok(0b01001));
+        assert_eq!(process_bus(input, "out"), Ok(0b101));
+        assert_eq!(process_part1_sat(input), Ok(0b01001));
+        assert_eq!(process_bus(input, "none"), Ok(0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_circuit("a: 2\n\na AND a -> z00").err(),
+            Some(ParseError::InvalidValue {
+                line: 1,
+                value: "2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_circuit("a: 1\n\na IMPLIES a -> z00").err(),
+            Some(ParseError::UnknownGate {
+                line: 3,
+                gate: "IMPLIES".to_string()
+            })
+        );
+        assert_eq!(
+            parse_circuit("a: 1\n\na AND -> z00").err(),
+            Some(ParseError::Malformed { line: 3 })
+        );
+        assert_eq!(
+            parse_circuit("a: 1\n\na AND a z00").err(),
+            Some(ParseError::Malformed { line: 3 })
+        );
+        assert_eq!(
+            process_part1("a: 1\n\na IMPLIES a -> z00"),
+            Err(CircuitError::Parse(ParseError::UnknownGate {
+                line: 3,
+                gate: "IMPLIES".to_string()
+            }))
+        );
     }
 
     #[test]
+    #[ignore = "part 2 is not solved yet and the expected value is a placeholder"]
     fn test_process_part2() {
         assert_eq!(process_part2(EXAMPLE), "co,de,ka,ta");
     }